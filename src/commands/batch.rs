@@ -0,0 +1,191 @@
+use crate::commands::plan::{PlannedTransfer, Rejections};
+use crate::commands::transaction::capitalize;
+use crate::error::{FugaError, FugaResult};
+use crate::fuga::{OperationKind, TransferOutcome};
+use crate::traits::UIService;
+
+/// What happened to a single target of a batch
+#[derive(Debug)]
+pub enum TargetStatus {
+    Succeeded,
+    Skipped,
+    Failed(FugaError),
+}
+
+/// Per-target result of a batch
+#[derive(Debug)]
+pub struct TargetReport {
+    pub source: String,
+    pub destination: String,
+    pub status: TargetStatus,
+}
+
+/// Collected per-target results of a copy, move, or link batch
+#[derive(Debug, Default)]
+pub struct BatchReport {
+    pub targets: Vec<TargetReport>,
+}
+
+impl BatchReport {
+    fn push(&mut self, entry: &PlannedTransfer, destination: &str, status: TargetStatus) {
+        self.targets.push(TargetReport {
+            source: entry.source.clone(),
+            destination: destination.to_string(),
+            status,
+        });
+    }
+
+    pub fn failed(&self) -> usize {
+        self.targets
+            .iter()
+            .filter(|target| matches!(target.status, TargetStatus::Failed(_)))
+            .count()
+    }
+
+    /// Sources that are still in place, either skipped or failed
+    pub fn unfinished_sources(&self) -> Vec<String> {
+        self.targets
+            .iter()
+            .filter(|target| !matches!(target.status, TargetStatus::Succeeded))
+            .map(|target| target.source.clone())
+            .collect()
+    }
+
+    /// Error describing the failed targets, if there were any
+    pub fn failure(&self) -> Option<FugaError> {
+        let failed = self.failed();
+        (failed > 0).then_some(FugaError::BatchIncomplete {
+            failed,
+            total: self.targets.len(),
+        })
+    }
+
+    /// Print one line per target with its status
+    pub fn print_summary(&self, kind: OperationKind, ui_service: &dyn UIService) {
+        let count = |wanted: fn(&TargetStatus) -> bool| {
+            self.targets
+                .iter()
+                .filter(|target| wanted(&target.status))
+                .count()
+        };
+        println!(
+            "{} : {} summary: {} succeeded, {} skipped, {} failed.",
+            ui_service.get_icon_information(),
+            capitalize(kind.verb()),
+            count(|status| matches!(status, TargetStatus::Succeeded)),
+            count(|status| matches!(status, TargetStatus::Skipped)),
+            self.failed()
+        );
+
+        for target in &self.targets {
+            match &target.status {
+                TargetStatus::Succeeded => println!(
+                    "  succeeded  {} -> {}",
+                    target.source,
+                    ui_service.get_colorized_text(&target.destination, false)
+                ),
+                TargetStatus::Skipped => {
+                    println!("  skipped    {} -> {}", target.source, target.destination)
+                }
+                TargetStatus::Failed(err) => {
+                    println!("  failed     {}: {}", target.source, err)
+                }
+            }
+        }
+    }
+}
+
+/// Run `step` for every planned target
+///
+/// Without `keep_going` the first error aborts the batch; with it the error is
+/// reported, recorded, and the remaining targets are still attempted. Targets
+/// in `rejected` failed the pre-flight check and count as failed without
+/// running `step`. A cancellation always stops the batch and lists every
+/// target left undone.
+pub(crate) fn run_batch<F>(
+    plan: &[PlannedTransfer],
+    keep_going: bool,
+    mut rejected: Rejections,
+    mut step: F,
+) -> FugaResult<BatchReport>
+where
+    F: FnMut(&PlannedTransfer) -> FugaResult<TransferOutcome>,
+{
+    let mut report = BatchReport::default();
+    for (index, entry) in plan.iter().enumerate() {
+        let result = match rejected.remove(&entry.source) {
+            Some(problem) => Err(problem),
+            None => step(entry),
+        };
+        match result {
+            Ok(outcome) if outcome.skipped => {
+                report.push(entry, &outcome.destination, TargetStatus::Skipped)
+            }
+            Ok(outcome) => report.push(entry, &outcome.destination, TargetStatus::Succeeded),
+            Err(err) if err.is_cancelled() => {
+                let mut remaining = report.unfinished_sources();
+                remaining.extend(plan[index..].iter().map(|entry| entry.source.clone()));
+                return Err(FugaError::Cancelled { remaining });
+            }
+            Err(err) if keep_going => {
+                eprintln!("❌ : {err}");
+                report.push(entry, &entry.destination, TargetStatus::Failed(err));
+            }
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fuga::FileInfo;
+
+    fn planned(source: &str) -> PlannedTransfer {
+        PlannedTransfer {
+            source: source.to_string(),
+            info: FileInfo {
+                exists: true,
+                is_file: true,
+                is_dir: false,
+                is_symlink: false,
+                size: 0,
+                modified: None,
+                name: Some(source.to_string()),
+            },
+            destination: format!("copy-of-{source}"),
+            destination_exists: false,
+            destination_is_dir: false,
+        }
+    }
+
+    #[test]
+    fn cancellation_stops_even_a_keep_going_batch_and_lists_what_is_left() {
+        let plan: Vec<_> = ["a", "b", "c", "d"].iter().map(|s| planned(s)).collect();
+        let mut attempted = Vec::new();
+
+        let result = run_batch(&plan, true, Rejections::new(), |entry| {
+            attempted.push(entry.source.clone());
+            match entry.source.as_str() {
+                "a" => Err(FugaError::OperationFailed("broken".to_string())),
+                "b" => Ok(TransferOutcome {
+                    destination: entry.destination.clone(),
+                    backup: None,
+                    skipped: false,
+                    cross_device: false,
+                    prior: Default::default(),
+                }),
+                _ => Err(FugaError::Cancelled {
+                    remaining: Vec::new(),
+                }),
+            }
+        });
+
+        assert_eq!(attempted, ["a", "b", "c"]);
+        match result {
+            Err(FugaError::Cancelled { remaining }) => assert_eq!(remaining, ["a", "c", "d"]),
+            other => panic!("expected a cancellation, got {other:?}"),
+        }
+    }
+}
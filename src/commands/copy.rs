@@ -1,7 +1,17 @@
-use crate::commands::{Command, CommandResult};
-use crate::error::FugaError;
-use crate::fuga::TargetType;
+use crate::commands::batch::{run_batch, BatchReport};
+use crate::commands::history::format_timestamp;
+use crate::commands::plan::{
+    plan_transfers, print_plan, validate_plan, PlannedTransfer, Rejections,
+};
+use crate::commands::transaction::{unix_timestamp, Transaction};
+use crate::commands::{
+    confirm_conflict_policy, report_conflict_resolution, Command, CommandResult, TransferArgs,
+};
+use crate::config::UnfinishedCopy;
+use crate::error::{FugaError, FugaResult};
+use crate::fuga::{OperationKind, PathFilters, TransferOptions, TransferOutcome};
 use crate::traits::{ConfigRepository, FileSystemService, PathService, UIService};
+use std::path::{Path, PathBuf};
 
 /// Copy command for copying marked files/directories
 pub struct CopyCommand<'a> {
@@ -10,6 +20,7 @@ pub struct CopyCommand<'a> {
     ui_service: &'a dyn UIService,
     path_service: &'a dyn PathService,
     destination: Option<String>,
+    args: TransferArgs,
 }
 
 impl<'a> CopyCommand<'a> {
@@ -19,6 +30,7 @@ impl<'a> CopyCommand<'a> {
         ui_service: &'a dyn UIService,
         path_service: &'a dyn PathService,
         destination: Option<String>,
+        args: TransferArgs,
     ) -> Self {
         Self {
             config_repo,
@@ -26,6 +38,7 @@ impl<'a> CopyCommand<'a> {
             ui_service,
             path_service,
             destination,
+            args,
         }
     }
 }
@@ -37,7 +50,27 @@ impl<'a> Command for CopyCommand<'a> {
             return Err(FugaError::OperationFailed("No targets marked.".to_string()));
         }
 
+        let options = self
+            .args
+            .resolve_options(OperationKind::Copy, self.config_repo)?;
         let destination_arg = self.destination.as_deref();
+        let destination = self
+            .fs_service
+            .get_abs_path(destination_arg.unwrap_or("."))?;
+        let unfinished = UnfinishedCopy {
+            targets: targets.clone(),
+            // Component iteration drops the trailing `.` of the current directory
+            destination: Path::new(&destination)
+                .components()
+                .collect::<PathBuf>()
+                .to_string_lossy()
+                .into_owned(),
+            timestamp: unix_timestamp(),
+        };
+        if !self.args.resume {
+            self.suggest_resume(&unfinished)?;
+        }
+
         let destination_accepts_many = match destination_arg {
             Some(dest) => {
                 let info = self.fs_service.get_file_info(dest)?;
@@ -52,43 +85,147 @@ impl<'a> Command for CopyCommand<'a> {
             ));
         }
 
-        for target in targets {
-            let info = self.fs_service.get_file_info(&target)?;
-            if !info.exists {
-                return Err(FugaError::FileNotFound(target));
-            }
-
-            let target_type = if info.is_file {
-                TargetType::File
-            } else {
-                TargetType::Dir
-            };
-
-            let dst_name = self.path_service.get_destination_name_with_info(
-                &target,
-                &info,
-                destination_arg,
+        let plan = plan_transfers(
+            &targets,
+            destination_arg,
+            self.fs_service,
+            self.path_service,
+        )?;
+        if self.args.dry_run {
+            print_plan(
+                &plan,
+                OperationKind::Copy,
+                &options,
                 self.fs_service,
+                self.ui_service,
             )?;
+        }
+        let rejected = validate_plan(
+            &plan,
+            OperationKind::Copy,
+            &options,
+            self.args.keep_going,
+            self.fs_service,
+        )?;
+        if self.args.dry_run {
+            return Ok(());
+        }
 
-            println!(
-                "{} : Copying {} {} -> {}",
-                self.ui_service.get_icon_information(),
-                self.ui_service.get_icon_for_target_type(target_type),
-                self.ui_service.get_colorized_text(&target, true),
-                self.ui_service.get_colorized_text(&dst_name, true)
-            );
-
-            self.fs_service.copy_items(&target, &dst_name)?;
-
-            let dst_type = self.fs_service.get_file_type(&dst_name);
-            println!(
-                "✅ : {} {} copied.",
-                self.ui_service.get_icon_for_target_type(dst_type),
-                self.ui_service.get_colorized_text(&dst_name, true)
-            );
+        // Recorded before any data is written so a crash leaves it behind too
+        self.config_repo.set_unfinished_copy(Some(&unfinished))?;
+        let mut transaction = Transaction::new(self.fs_service, OperationKind::Copy, plan.len());
+        if plan.len() > 1 {
+            let mark_filters = self.config_repo.get_mark_filters()?;
+            let sources: Vec<(String, PathFilters)> = plan
+                .iter()
+                .filter(|entry| !rejected.contains_key(&entry.source))
+                .map(|entry| {
+                    let options = options.with_mark_filters(mark_filters.get(&entry.source));
+                    (entry.source.clone(), options.filters)
+                })
+                .collect();
+            self.fs_service
+                .begin_progress(&sources, options.dereference)?;
+        }
+        let result = self.run_plan(&plan, rejected, &options, &mut transaction);
+        self.fs_service.end_progress();
+        let report =
+            transaction.finish(self.config_repo, self.ui_service, result, self.args.atomic)?;
+        if report.failure().is_none() {
+            self.config_repo.set_unfinished_copy(None)?;
         }
+        self.args
+            .conclude(&report, OperationKind::Copy, self.ui_service)
+    }
+}
 
+impl<'a> CopyCommand<'a> {
+    /// Point out `--resume` when the last copy of the same mark list did not finish
+    fn suggest_resume(&self, current: &UnfinishedCopy) -> FugaResult<()> {
+        let Some(previous) = self.config_repo.get_unfinished_copy()? else {
+            return Ok(());
+        };
+        if previous.targets != current.targets {
+            return Ok(());
+        }
+
+        println!(
+            "{} : A copy of these targets to {} started at {} did not finish. Add --resume to continue it instead of starting over.",
+            self.ui_service.get_icon_information(),
+            self.ui_service
+                .get_colorized_text(&previous.destination, true),
+            format_timestamp(previous.timestamp)
+        );
         Ok(())
     }
+
+    fn run_plan(
+        &self,
+        plan: &[PlannedTransfer],
+        rejected: Rejections,
+        options: &TransferOptions,
+        transaction: &mut Transaction,
+    ) -> FugaResult<BatchReport> {
+        let mark_filters = self.config_repo.get_mark_filters()?;
+        run_batch(plan, self.args.keep_going, rejected, |entry| {
+            let options = options.with_mark_filters(mark_filters.get(&entry.source));
+            self.copy_target(entry, &options, transaction)
+        })
+    }
+
+    fn copy_target(
+        &self,
+        entry: &PlannedTransfer,
+        options: &TransferOptions,
+        transaction: &mut Transaction,
+    ) -> FugaResult<TransferOutcome> {
+        self.fs_service.check_cancelled()?;
+        let target = &entry.source;
+        let dst_name = &entry.destination;
+
+        println!(
+            "{} : Copying {} {} -> {}",
+            self.ui_service.get_icon_information(),
+            self.ui_service
+                .get_icon_for_target_type(entry.target_type()),
+            self.ui_service.get_colorized_text(target, true),
+            self.ui_service.get_colorized_text(dst_name, true)
+        );
+
+        let on_conflict =
+            confirm_conflict_policy(self.fs_service, self.ui_service, dst_name, options)?;
+        let outcome =
+            self.fs_service
+                .copy_items(target, dst_name, &options.with_conflict(on_conflict))?;
+        transaction.record(target, &outcome)?;
+        report_conflict_resolution(self.ui_service, &outcome);
+        if outcome.skipped {
+            return Ok(outcome);
+        }
+
+        // Verified only once journaled, so a copy that fails is never left untracked
+        let mismatches = self
+            .fs_service
+            .verify_copy(target, &outcome.destination, options)?;
+        if !mismatches.is_empty() {
+            return Err(FugaError::ChecksumMismatch {
+                destination: outcome.destination.clone(),
+                files: mismatches,
+                outcome: match options.cleanup_on_failure {
+                    true => "The copy is rolled back with the rest of the batch.",
+                    false => "The copy was kept and journaled, so `fuga undo` can remove it.",
+                }
+                .to_string(),
+            });
+        }
+
+        let dst_type = self.fs_service.get_file_type(&outcome.destination);
+        println!(
+            "✅ : {} {} copied.",
+            self.ui_service.get_icon_for_target_type(dst_type),
+            self.ui_service
+                .get_colorized_text(&outcome.destination, true)
+        );
+        Ok(outcome)
+    }
 }
@@ -0,0 +1,264 @@
+use crate::commands::{Command, CommandResult};
+use crate::config::JournalEntry;
+use crate::error::FugaError;
+use crate::fuga::{OperationKind, OperationStep};
+use crate::traits::{ConfigRepository, UIService};
+use serde::Serialize;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// History command for listing journaled copy, move, and link operations
+pub struct HistoryCommand<'a> {
+    config_repo: &'a dyn ConfigRepository,
+    ui_service: &'a dyn UIService,
+    limit: Option<usize>,
+    since: Option<u64>,
+    json: bool,
+}
+
+/// JSON shape of a single history entry
+#[derive(Serialize)]
+struct HistoryRecord<'a> {
+    timestamp: u64,
+    time: String,
+    command: OperationKind,
+    targets: usize,
+    status: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<&'a str>,
+    undone: bool,
+    steps: &'a [OperationStep],
+}
+
+impl<'a> HistoryCommand<'a> {
+    pub fn new(
+        config_repo: &'a dyn ConfigRepository,
+        ui_service: &'a dyn UIService,
+        limit: Option<usize>,
+        since: Option<u64>,
+        json: bool,
+    ) -> Self {
+        Self {
+            config_repo,
+            ui_service,
+            limit,
+            since,
+            json,
+        }
+    }
+
+    /// Journal entries matching the filters, newest first
+    fn select_entries(&self, journal: &'a [JournalEntry]) -> Vec<&'a JournalEntry> {
+        journal
+            .iter()
+            .rev()
+            .filter(|entry| self.since.is_none_or(|since| entry.timestamp >= since))
+            .take(self.limit.unwrap_or(usize::MAX))
+            .collect()
+    }
+
+    fn print_json(&self, entries: &[&JournalEntry]) -> CommandResult {
+        let records: Vec<HistoryRecord> = entries
+            .iter()
+            .map(|entry| HistoryRecord {
+                timestamp: entry.timestamp,
+                time: format_timestamp(entry.timestamp),
+                command: entry.kind,
+                targets: entry.targets,
+                status: if entry.error.is_none() {
+                    "success"
+                } else {
+                    "failed"
+                },
+                error: entry.error.as_deref(),
+                undone: entry.undone,
+                steps: &entry.steps,
+            })
+            .collect();
+
+        let text = serde_json::to_string_pretty(&records)
+            .map_err(|e| FugaError::OperationFailed(format!("Failed to serialize history: {e}")))?;
+        println!("{text}");
+        Ok(())
+    }
+
+    fn print_text(&self, entries: &[&JournalEntry]) -> CommandResult {
+        if entries.is_empty() {
+            println!(
+                "{} : No operations recorded.",
+                self.ui_service.get_icon_information()
+            );
+            return Ok(());
+        }
+
+        println!(
+            "{} : Operation history (newest first):",
+            self.ui_service.get_icon_information()
+        );
+        for entry in entries {
+            let status = match &entry.error {
+                None => "succeeded".to_string(),
+                Some(error) => format!("failed: {error}"),
+            };
+            let undone = if entry.undone { " (undone)" } else { "" };
+            println!(
+                "{}  {:<4}  {} target(s)  {}{}",
+                format_timestamp(entry.timestamp),
+                entry.kind.verb(),
+                entry.targets,
+                status,
+                undone
+            );
+            for step in &entry.steps {
+                println!(
+                    "    {} -> {}",
+                    step.source,
+                    self.ui_service.get_colorized_text(&step.destination, false)
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'a> Command for HistoryCommand<'a> {
+    fn execute(&self) -> CommandResult {
+        let journal = self.config_repo.get_journal()?;
+        let entries = self.select_entries(&journal);
+        if self.json {
+            self.print_json(&entries)
+        } else {
+            self.print_text(&entries)
+        }
+    }
+}
+
+/// Parse `--since` as `YYYY-MM-DD`, `YYYY-MM-DD HH:MM[:SS]` (UTC), or an age like `90m`, `2h`, `3d`, `1w`
+pub fn parse_since(value: &str) -> Result<u64, String> {
+    let value = value.trim();
+    let invalid = || {
+        format!("invalid time '{value}', expected YYYY-MM-DD[ HH:MM[:SS]] or an age like 2h, 3d")
+    };
+
+    if let Some(unit) = value.chars().last().filter(char::is_ascii_alphabetic) {
+        let amount: u64 = value[..value.len() - 1].parse().map_err(|_| invalid())?;
+        let seconds = match unit {
+            's' => 1,
+            'm' => 60,
+            'h' => 60 * 60,
+            'd' => 24 * 60 * 60,
+            'w' => 7 * 24 * 60 * 60,
+            _ => return Err(invalid()),
+        };
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or_default();
+        return Ok(now.saturating_sub(amount.saturating_mul(seconds)));
+    }
+
+    let (date, time) = match value.split_once([' ', 'T']) {
+        Some((date, time)) => (date, Some(time)),
+        None => (value, None),
+    };
+
+    let mut date_parts = date.splitn(3, '-').map(|part| part.parse::<u64>());
+    let (Some(Ok(year)), Some(Ok(month)), Some(Ok(day))) =
+        (date_parts.next(), date_parts.next(), date_parts.next())
+    else {
+        return Err(invalid());
+    };
+    if year < 1970 || !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return Err(invalid());
+    }
+
+    let mut seconds_of_day = 0;
+    if let Some(time) = time {
+        let parts: Vec<u64> = time
+            .split(':')
+            .map(|part| part.parse::<u64>().map_err(|_| invalid()))
+            .collect::<Result<_, _>>()?;
+        let (hour, minute, second) = match parts.as_slice() {
+            [hour, minute] => (*hour, *minute, 0),
+            [hour, minute, second] => (*hour, *minute, *second),
+            _ => return Err(invalid()),
+        };
+        if hour > 23 || minute > 59 || second > 59 {
+            return Err(invalid());
+        }
+        seconds_of_day = hour * 3600 + minute * 60 + second;
+    }
+
+    Ok(days_from_civil(year, month, day) * 86_400 + seconds_of_day)
+}
+
+/// Format seconds since the Unix epoch as `YYYY-MM-DD HH:MM:SS UTC`
+pub(crate) fn format_timestamp(timestamp: u64) -> String {
+    let (year, month, day) = civil_from_days(timestamp / 86_400);
+    let seconds = timestamp % 86_400;
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02}:{:02} UTC",
+        seconds / 3600,
+        seconds % 3600 / 60,
+        seconds % 60
+    )
+}
+
+/// Days since 1970-01-01 for a proleptic Gregorian date (Howard Hinnant's algorithm)
+fn days_from_civil(year: u64, month: u64, day: u64) -> u64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year / 400;
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Inverse of [`days_from_civil`]
+fn civil_from_days(days: u64) -> (u64, u64, u64) {
+    let days = days + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_timestamp_renders_utc_calendar_time() {
+        assert_eq!(format_timestamp(0), "1970-01-01 00:00:00 UTC");
+        assert_eq!(format_timestamp(1_709_210_096), "2024-02-29 12:34:56 UTC");
+    }
+
+    #[test]
+    fn parse_since_accepts_dates_and_times() {
+        assert_eq!(parse_since("2024-02-29"), Ok(1_709_164_800));
+        assert_eq!(parse_since("2024-02-29 12:34:56"), Ok(1_709_210_096));
+        assert_eq!(parse_since("2024-02-29T12:34"), Ok(1_709_210_040));
+    }
+
+    #[test]
+    fn parse_since_accepts_relative_ages() {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let since = parse_since("2h").unwrap();
+        assert!(now - since >= 7200 && now - since < 7260);
+    }
+
+    #[test]
+    fn parse_since_rejects_garbage() {
+        assert!(parse_since("yesterday").is_err());
+        assert!(parse_since("2024-13-01").is_err());
+        assert!(parse_since("5y").is_err());
+    }
+}
@@ -1,6 +1,13 @@
-use crate::commands::{Command, CommandResult};
-use crate::error::FugaError;
-use crate::fuga::TargetType;
+use crate::commands::batch::{run_batch, BatchReport};
+use crate::commands::plan::{
+    plan_transfers, print_plan, validate_plan, PlannedTransfer, Rejections,
+};
+use crate::commands::transaction::Transaction;
+use crate::commands::{
+    confirm_conflict_policy, report_conflict_resolution, Command, CommandResult, TransferArgs,
+};
+use crate::error::{FugaError, FugaResult};
+use crate::fuga::{OperationKind, TransferOptions, TransferOutcome};
 use crate::traits::{ConfigRepository, FileSystemService, PathService, UIService};
 
 /// Link command for creating symbolic links to marked files/directories
@@ -10,6 +17,7 @@ pub struct LinkCommand<'a> {
     ui_service: &'a dyn UIService,
     path_service: &'a dyn PathService,
     destination: Option<String>,
+    args: TransferArgs,
 }
 
 impl<'a> LinkCommand<'a> {
@@ -19,6 +27,7 @@ impl<'a> LinkCommand<'a> {
         ui_service: &'a dyn UIService,
         path_service: &'a dyn PathService,
         destination: Option<String>,
+        args: TransferArgs,
     ) -> Self {
         Self {
             config_repo,
@@ -26,6 +35,7 @@ impl<'a> LinkCommand<'a> {
             ui_service,
             path_service,
             destination,
+            args,
         }
     }
 }
@@ -37,6 +47,9 @@ impl<'a> Command for LinkCommand<'a> {
             return Err(FugaError::OperationFailed("No targets marked.".to_string()));
         }
 
+        let options = self
+            .args
+            .resolve_options(OperationKind::Link, self.config_repo)?;
         let destination_arg = self.destination.as_deref();
         let destination_accepts_many = match destination_arg {
             Some(dest) => {
@@ -52,43 +65,101 @@ impl<'a> Command for LinkCommand<'a> {
             ));
         }
 
-        for target in targets {
-            let info = self.fs_service.get_file_info(&target)?;
-            if !info.exists {
-                return Err(FugaError::FileNotFound(target));
-            }
-
-            let target_type = if info.is_file {
-                TargetType::File
-            } else {
-                TargetType::Dir
-            };
-
-            let dst_name = self.path_service.get_destination_name_with_info(
-                &target,
-                &info,
-                destination_arg,
+        let plan = plan_transfers(
+            &targets,
+            destination_arg,
+            self.fs_service,
+            self.path_service,
+        )?;
+        if self.args.dry_run {
+            print_plan(
+                &plan,
+                OperationKind::Link,
+                &options,
                 self.fs_service,
+                self.ui_service,
             )?;
+        }
+        let rejected = validate_plan(
+            &plan,
+            OperationKind::Link,
+            &options,
+            self.args.keep_going,
+            self.fs_service,
+        )?;
+        if self.args.dry_run {
+            return Ok(());
+        }
 
-            println!(
-                "{} : Linking {} {} -> {}",
-                self.ui_service.get_icon_information(),
-                self.ui_service.get_icon_for_target_type(target_type),
-                self.ui_service.get_colorized_text(&target, true),
-                self.ui_service.get_colorized_text(&dst_name, true)
-            );
+        let mut transaction = Transaction::new(self.fs_service, OperationKind::Link, plan.len());
+        let result = self.run_plan(&plan, rejected, &options, &mut transaction);
+        let report =
+            transaction.finish(self.config_repo, self.ui_service, result, self.args.atomic)?;
+        self.args
+            .conclude(&report, OperationKind::Link, self.ui_service)
+    }
+}
 
-            self.fs_service.link_items(&target, &dst_name)?;
+impl<'a> LinkCommand<'a> {
+    fn run_plan(
+        &self,
+        plan: &[PlannedTransfer],
+        rejected: Rejections,
+        options: &TransferOptions,
+        transaction: &mut Transaction,
+    ) -> FugaResult<BatchReport> {
+        run_batch(plan, self.args.keep_going, rejected, |entry| {
+            self.link_target(entry, options, transaction)
+        })
+    }
 
-            let dst_type = self.fs_service.get_file_type(&dst_name);
-            println!(
-                "✅ : {} {} linked.",
-                self.ui_service.get_icon_for_target_type(dst_type),
-                self.ui_service.get_colorized_text(&dst_name, true)
-            );
+    fn link_target(
+        &self,
+        entry: &PlannedTransfer,
+        options: &TransferOptions,
+        transaction: &mut Transaction,
+    ) -> FugaResult<TransferOutcome> {
+        self.fs_service.check_cancelled()?;
+        let target = &entry.source;
+        let dst_name = &entry.destination;
+
+        println!(
+            "{} : {} {} {} -> {}",
+            self.ui_service.get_icon_information(),
+            if options.hard_link {
+                "Hard-linking"
+            } else {
+                "Linking"
+            },
+            self.ui_service
+                .get_icon_for_target_type(entry.target_type()),
+            self.ui_service.get_colorized_text(target, true),
+            self.ui_service.get_colorized_text(dst_name, true)
+        );
+
+        let on_conflict =
+            confirm_conflict_policy(self.fs_service, self.ui_service, dst_name, options)?;
+        let outcome =
+            self.fs_service
+                .link_items(target, dst_name, &options.with_conflict(on_conflict))?;
+        transaction.record(target, &outcome)?;
+        report_conflict_resolution(self.ui_service, &outcome);
+        if outcome.skipped {
+            return Ok(outcome);
         }
 
-        Ok(())
+        let dst_type = self.fs_service.get_file_type(&outcome.destination);
+        println!(
+            "✅ : {} {} {}.",
+            self.ui_service.get_icon_for_target_type(dst_type),
+            self.ui_service
+                .get_colorized_text(&outcome.destination, true),
+            if options.hard_link {
+                "hard-linked"
+            } else {
+                "linked"
+            }
+        );
+        Ok(outcome)
     }
 }
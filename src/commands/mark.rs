@@ -1,8 +1,16 @@
+use crate::commands::history::format_timestamp;
+use crate::commands::transaction::unix_timestamp;
 use crate::commands::{Command, CommandResult};
+use crate::config::MarkRecord;
 use crate::error::{FugaError, FugaResult};
-use crate::fuga::{FileInfo, TargetType};
+use crate::fuga::{EntryType, FileInfo, PathFilters, TargetType};
 use crate::traits::{ConfigRepository, FileSystemService, UIService};
+use globset::{Glob, GlobBuilder, GlobSet, GlobSetBuilder};
+use indicatif::HumanBytes;
 use std::collections::HashSet;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::{Component, Path, PathBuf};
 
 /// Mark command for managing marked paths
 pub struct MarkCommand<'a> {
@@ -10,28 +18,114 @@ pub struct MarkCommand<'a> {
     fs_service: &'a dyn FileSystemService,
     ui_service: &'a dyn UIService,
     action: MarkAction,
+    args: MarkArgs,
 }
 
 #[derive(Debug)]
 pub enum MarkAction {
     Set(Vec<String>),
     Add(Vec<String>),
-    List,
+    /// Unmark paths, 1-based positions, or globs matched against the marked paths
+    Remove(Vec<String>),
+    /// Unmark the marked paths and mark the others
+    Toggle(Vec<String>),
+    List(ListStyle),
+    /// Report marks whose path is gone or changed type since it was marked
+    Check,
+    /// Unmark what `Check` reports
+    Prune,
     Reset,
 }
 
+/// How `mark --list` shows each mark
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListStyle {
+    /// Type icon and path
+    Short,
+    /// Adds what was recorded when the path was marked
+    Long,
+    /// Only the paths, each followed by `terminator`, for `xargs`
+    Bare { terminator: char },
+}
+
+/// Details stored with the paths a mark command marks
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MarkArgs {
+    /// Filters to remember for the marked directories
+    pub filters: PathFilters,
+    /// Free-form note shown by `mark --list --long`
+    pub note: Option<String>,
+    /// Look for the entries to mark instead of taking the paths as given
+    pub search: MarkSearch,
+    /// Read more paths to mark from stdin or a file
+    pub path_list: Option<PathList>,
+}
+
+/// Where `--stdin` and `--from-file` read paths from
+#[derive(Debug, Clone, PartialEq)]
+pub struct PathList {
+    /// File to read, or stdin when `None`
+    pub file: Option<String>,
+    /// Paths end with NUL bytes instead of newlines, as `find -print0` writes them
+    pub nul: bool,
+}
+
+/// What `--glob` and `--recursive` look for
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MarkSearch {
+    /// Patterns matched against full paths, e.g. `**/*.log`
+    pub globs: Vec<String>,
+    /// Search below the given directories (or the current one) instead of marking them
+    pub recursive: bool,
+    /// Entry name patterns, any of which must match
+    pub names: Vec<String>,
+    pub entry_type: Option<EntryType>,
+    /// Only entries modified at or after this Unix time
+    pub newer: Option<u64>,
+    /// Only entries larger than this many bytes
+    pub larger: Option<u64>,
+}
+
+impl MarkSearch {
+    pub fn is_active(&self) -> bool {
+        self.recursive || !self.globs.is_empty()
+    }
+
+    fn accepts(&self, names: &GlobSet, info: &FileInfo) -> bool {
+        let name_matches = self.names.is_empty()
+            || info
+                .name
+                .as_deref()
+                .is_some_and(|name| names.is_match(name));
+        let type_matches = match self.entry_type {
+            Some(EntryType::File) => info.target_type() == TargetType::File,
+            Some(EntryType::Dir) => info.target_type() == TargetType::Dir,
+            Some(EntryType::Symlink) => info.is_symlink,
+            None => true,
+        };
+        name_matches
+            && type_matches
+            && self
+                .newer
+                .is_none_or(|since| info.modified.is_some_and(|modified| modified >= since))
+            && self.larger.is_none_or(|size| info.size > size)
+    }
+}
+
 impl<'a> MarkCommand<'a> {
     pub fn new(
         config_repo: &'a dyn ConfigRepository,
         fs_service: &'a dyn FileSystemService,
         ui_service: &'a dyn UIService,
         action: MarkAction,
+        args: MarkArgs,
     ) -> Self {
         Self {
             config_repo,
             fs_service,
             ui_service,
             action,
+            args,
         }
     }
 }
@@ -41,7 +135,11 @@ impl<'a> Command for MarkCommand<'a> {
         match &self.action {
             MarkAction::Set(paths) => self.set_targets(paths),
             MarkAction::Add(paths) => self.add_targets(paths),
-            MarkAction::List => self.list_targets(),
+            MarkAction::List(style) => self.list_targets(*style),
+            MarkAction::Remove(selectors) => self.remove_targets(selectors),
+            MarkAction::Toggle(paths) => self.toggle_targets(paths),
+            MarkAction::Check => self.check_targets(),
+            MarkAction::Prune => self.prune_targets(),
             MarkAction::Reset => self.reset_targets(),
         }
     }
@@ -50,19 +148,30 @@ impl<'a> Command for MarkCommand<'a> {
 impl<'a> MarkCommand<'a> {
     fn set_targets(&self, paths: &[String]) -> CommandResult {
         let resolved = self.prepare_targets(paths)?;
+        if resolved.is_empty() {
+            return self.print_nothing_found();
+        }
         let unique = Self::dedupe_preserving_order(resolved.iter().map(|(abs, _)| abs.clone()));
-        self.config_repo.set_marked_targets(&unique)?;
+        let origin = self.origin();
+        let records: Vec<MarkRecord> = unique
+            .iter()
+            .filter_map(|path| resolved.iter().find(|(abs, _)| abs == path))
+            .map(|(abs, info)| self.record(abs, info, &origin))
+            .collect();
+        self.config_repo.set_mark_records(&records)?;
+        self.store_filters(&resolved)?;
 
         let unique_lookup: HashSet<String> = unique.iter().cloned().collect();
         let mut printed = HashSet::new();
         for (abs_path, info) in resolved {
             if unique_lookup.contains(&abs_path) && printed.insert(abs_path.clone()) {
-                let target_type = Self::target_type_from_info(&info);
+                let target_type = info.target_type();
                 println!(
                     "✅ : {} {} marked.",
                     self.ui_service.get_icon_for_target_type(target_type),
                     self.ui_service.get_colorized_text(&abs_path, true)
                 );
+                self.print_filters(&info);
             }
         }
 
@@ -71,23 +180,33 @@ impl<'a> MarkCommand<'a> {
 
     fn add_targets(&self, paths: &[String]) -> CommandResult {
         let resolved = self.prepare_targets(paths)?;
-        let existing = self.config_repo.get_marked_targets()?;
+        if resolved.is_empty() {
+            return self.print_nothing_found();
+        }
+        let existing = self.config_repo.get_mark_records()?;
         let mut seen_all: HashSet<String> = HashSet::new();
         let mut current = Vec::with_capacity(existing.len() + resolved.len());
-        for value in existing {
-            if seen_all.insert(value.clone()) {
-                current.push(value);
+        for record in existing {
+            if seen_all.insert(record.path.clone()) {
+                current.push(record);
             }
         }
 
+        let origin = self.origin();
         let mut added = Vec::new();
-        for (abs_path, info) in resolved {
+        for (abs_path, info) in resolved.iter().cloned() {
             if seen_all.insert(abs_path.clone()) {
-                current.push(abs_path.clone());
+                current.push(self.record(&abs_path, &info, &origin));
                 added.push((abs_path, info));
+            } else if let Some(note) = &self.args.note {
+                // Already marked paths keep their record, but take a new note
+                if let Some(record) = current.iter_mut().find(|record| record.path == abs_path) {
+                    record.note = Some(note.clone());
+                }
             }
         }
-        self.config_repo.set_marked_targets(&current)?;
+        self.config_repo.set_mark_records(&current)?;
+        self.store_filters(&resolved)?;
 
         if added.is_empty() {
             println!(
@@ -96,21 +215,163 @@ impl<'a> MarkCommand<'a> {
             );
         } else {
             for (abs_path, info) in added {
-                let target_type = Self::target_type_from_info(&info);
+                let target_type = info.target_type();
                 println!(
                     "✅ : {} {} added.",
                     self.ui_service.get_icon_for_target_type(target_type),
                     self.ui_service.get_colorized_text(&abs_path, true)
                 );
+                self.print_filters(&info);
             }
         }
 
         self.print_list_summary(current.len())
     }
 
-    fn list_targets(&self) -> CommandResult {
-        let targets = self.config_repo.get_marked_targets()?;
-        if targets.is_empty() {
+    fn remove_targets(&self, selectors: &[String]) -> CommandResult {
+        let records = self.config_repo.get_mark_records()?;
+        let mut selected = vec![false; records.len()];
+        let mut unmatched = Vec::new();
+        for selector in selectors {
+            let matches = self.select(&records, selector)?;
+            if matches.is_empty() {
+                unmatched.push(selector);
+            }
+            for index in matches {
+                selected[index] = true;
+            }
+        }
+
+        let (removed, kept): (Vec<_>, Vec<_>) = records
+            .into_iter()
+            .zip(selected)
+            .partition(|(_, selected)| *selected);
+        let kept: Vec<MarkRecord> = kept.into_iter().map(|(record, _)| record).collect();
+        if !removed.is_empty() {
+            self.config_repo.set_mark_records(&kept)?;
+        }
+
+        for (record, _) in &removed {
+            self.print_unmarked(record);
+        }
+        for selector in unmatched {
+            println!(
+                "{} : {} was not marked.",
+                self.ui_service.get_icon_information(),
+                self.ui_service.get_colorized_text(selector, true)
+            );
+        }
+        self.print_list_summary(kept.len())
+    }
+
+    /// Indices of the marks a `--remove` argument refers to.
+    ///
+    /// A marked path wins over reading the argument as a number or a glob,
+    /// so files named `3` or `[a]` can still be unmarked.
+    fn select(&self, records: &[MarkRecord], selector: &str) -> FugaResult<Vec<usize>> {
+        let abs_path = self.fs_service.get_abs_path(selector)?;
+        if let Some(index) = records.iter().position(|record| record.path == abs_path) {
+            return Ok(vec![index]);
+        }
+        if let Ok(position) = selector.parse::<usize>() {
+            return Ok(match position {
+                1.. if position <= records.len() => vec![position - 1],
+                _ => Vec::new(),
+            });
+        }
+        if !selector.contains(GLOB_SYNTAX) {
+            return Ok(Vec::new());
+        }
+
+        let matcher = GlobBuilder::new(&abs_path)
+            .literal_separator(true)
+            .build()
+            .map_err(|e| invalid_pattern(selector, e))?
+            .compile_matcher();
+        Ok(records
+            .iter()
+            .enumerate()
+            .filter(|(_, record)| matcher.is_match(&record.path))
+            .map(|(index, _)| index)
+            .collect())
+    }
+
+    fn toggle_targets(&self, paths: &[String]) -> CommandResult {
+        let mut records = self.config_repo.get_mark_records()?;
+        let mut abs_paths = Vec::with_capacity(paths.len());
+        for path in paths {
+            abs_paths.push(self.fs_service.get_abs_path(path)?);
+        }
+        let abs_paths = Self::dedupe_preserving_order(abs_paths);
+
+        // Paths to mark must exist; check them all before changing anything
+        let mut to_mark = Vec::new();
+        for abs_path in &abs_paths {
+            if !records.iter().any(|record| &record.path == abs_path) {
+                let info = self.fs_service.get_file_info(abs_path)?;
+                if !info.exists {
+                    return Err(FugaError::FileNotFound(abs_path.clone()));
+                }
+                to_mark.push((abs_path.clone(), info));
+            }
+        }
+
+        let mut removed = Vec::new();
+        records.retain(|record| {
+            let toggled = abs_paths.contains(&record.path);
+            if toggled {
+                removed.push(record.clone());
+            }
+            !toggled
+        });
+        let origin = self.origin();
+        records.extend(
+            to_mark
+                .iter()
+                .map(|(abs_path, info)| self.record(abs_path, info, &origin)),
+        );
+        self.config_repo.set_mark_records(&records)?;
+        self.store_filters(&to_mark)?;
+
+        for record in &removed {
+            self.print_unmarked(record);
+        }
+        for (abs_path, info) in &to_mark {
+            println!(
+                "✅ : {} {} added.",
+                self.ui_service.get_icon_for_target_type(info.target_type()),
+                self.ui_service.get_colorized_text(abs_path, true)
+            );
+            self.print_filters(info);
+        }
+        self.print_list_summary(records.len())
+    }
+
+    fn print_unmarked(&self, record: &MarkRecord) {
+        let kind = record
+            .kind
+            .clone()
+            .unwrap_or_else(|| self.fs_service.get_file_type(&record.path));
+        println!(
+            "✅ : {} {} unmarked.",
+            self.ui_service.get_icon_for_target_type(kind),
+            self.ui_service.get_colorized_text(&record.path, true)
+        );
+    }
+
+    fn list_targets(&self, style: ListStyle) -> CommandResult {
+        let records = self.config_repo.get_mark_records()?;
+        if let ListStyle::Bare { terminator } = style {
+            let mut out = io::stdout().lock();
+            for record in records {
+                write!(out, "{}{}", record.path, terminator)?;
+            }
+            out.flush()?;
+            return Ok(());
+        }
+
+        let long = style == ListStyle::Long;
+        if records.is_empty() {
             println!(
                 "{} : No targets marked.",
                 self.ui_service.get_icon_information()
@@ -122,15 +383,115 @@ impl<'a> MarkCommand<'a> {
             "{} : Marked targets:",
             self.ui_service.get_icon_information()
         );
-        for target in targets {
-            let icon = self
-                .ui_service
-                .get_icon_for_target_type(self.fs_service.get_file_type(&target));
-            println!("{} {}", icon, target);
+        if long {
+            println!(
+                "{:<6} {:>10}  {:<23}  {:<23}  PATH",
+                "TYPE", "SIZE", "MODIFIED", "MARKED"
+            );
+        }
+        let mark_filters = self.config_repo.get_mark_filters()?;
+        for record in records {
+            let target = &record.path;
+            let current = self.fs_service.get_file_type(target);
+            // The long listing shows the type the path had when it was marked
+            let kind = match long {
+                true => record.kind.clone().unwrap_or(current),
+                false => current,
+            };
+            let icon = self.ui_service.get_icon_for_target_type(kind);
+            if long {
+                println!("{:<6} {}", icon, describe_record(&record));
+            } else {
+                println!("{} {}", icon, target);
+            }
+            if let Some(filters) = mark_filters.get(target) {
+                println!("   {}", describe_filters(filters));
+            }
         }
         Ok(())
     }
 
+    fn check_targets(&self) -> CommandResult {
+        let records = self.config_repo.get_mark_records()?;
+        let mut stale = 0;
+        for record in &records {
+            if let Some((current, reason)) = self.staleness(record) {
+                stale += 1;
+                println!(
+                    "{} : {} {} {}.",
+                    self.ui_service.get_icon_information(),
+                    self.ui_service.get_icon_for_target_type(current),
+                    self.ui_service.get_colorized_text(&record.path, true),
+                    reason
+                );
+            }
+        }
+
+        if stale == 0 {
+            println!(
+                "✅ : All {} marked target(s) are still in place.",
+                records.len()
+            );
+            return Ok(());
+        }
+        Err(FugaError::OperationFailed(format!(
+            "{stale} of {} mark(s) are stale. Run `fuga mark --prune` to drop them.",
+            records.len()
+        )))
+    }
+
+    fn prune_targets(&self) -> CommandResult {
+        let records = self.config_repo.get_mark_records()?;
+        let mut kept = Vec::with_capacity(records.len());
+        let mut pruned = Vec::new();
+        for record in records {
+            match self.staleness(&record) {
+                Some((current, reason)) => pruned.push((record, current, reason)),
+                None => kept.push(record),
+            }
+        }
+
+        if pruned.is_empty() {
+            println!(
+                "{} : No stale marks to prune.",
+                self.ui_service.get_icon_information()
+            );
+        } else {
+            self.config_repo.set_mark_records(&kept)?;
+            for (record, current, reason) in pruned {
+                println!(
+                    "✅ : {} {} unmarked, it {}.",
+                    self.ui_service.get_icon_for_target_type(current),
+                    self.ui_service.get_colorized_text(&record.path, true),
+                    reason
+                );
+            }
+        }
+        self.print_list_summary(kept.len())
+    }
+
+    /// The current type of a mark's path and why the mark is stale, if it is.
+    ///
+    /// Only paths confirmed missing count; a path that cannot be inspected
+    /// right now, say for lack of permission, keeps its mark.
+    fn staleness(&self, record: &MarkRecord) -> Option<(TargetType, String)> {
+        let current = self
+            .fs_service
+            .get_file_info(&record.path)
+            .ok()?
+            .target_type();
+        let reason = match &record.kind {
+            _ if current == TargetType::None => "is missing".to_string(),
+            Some(kind) if *kind != current => format!(
+                "was a {} and is now a {}",
+                type_name(kind),
+                type_name(&current)
+            ),
+            _ => return None,
+        };
+        Some((current, reason))
+    }
+
     fn reset_targets(&self) -> CommandResult {
         self.config_repo.reset_marks()?;
         println!("✅ : Marked targets cleared.");
@@ -138,7 +499,56 @@ impl<'a> MarkCommand<'a> {
         Ok(())
     }
 
+    /// Record for a path marked by this invocation
+    fn record(&self, path: &str, info: &FileInfo, origin: &Option<String>) -> MarkRecord {
+        MarkRecord {
+            note: self.args.note.clone(),
+            ..MarkRecord::new(path, info, unix_timestamp(), origin.clone())
+        }
+    }
+
+    /// Directory the marks are made from, kept to tell where a relative path came from
+    fn origin(&self) -> Option<String> {
+        self.clean_abs_path(".").ok()
+    }
+
+    /// Absolute path without `.` components, which component iteration drops
+    fn clean_abs_path(&self, path: &str) -> FugaResult<String> {
+        let abs_path = self.fs_service.get_abs_path(path)?;
+        let cleaned: PathBuf = Path::new(&abs_path).components().collect();
+        Ok(cleaned.to_string_lossy().into_owned())
+    }
+
+    /// Remember this invocation's filters for every marked directory, replacing earlier ones
+    fn store_filters(&self, resolved: &[(String, FileInfo)]) -> FugaResult<()> {
+        for (abs_path, info) in resolved {
+            if info.is_dir {
+                self.config_repo
+                    .set_mark_filters(abs_path, &self.args.filters)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn print_filters(&self, info: &FileInfo) {
+        if info.is_dir && !self.args.filters.is_empty() {
+            println!("   {}", describe_filters(&self.args.filters));
+        }
+    }
+
     fn prepare_targets(&self, paths: &[String]) -> FugaResult<Vec<(String, FileInfo)>> {
+        let listed;
+        let paths = match &self.args.path_list {
+            Some(list) => {
+                listed = [paths, &read_path_list(list)?].concat();
+                &listed[..]
+            }
+            None => paths,
+        };
+        if self.args.search.is_active() {
+            return self.search_targets(paths);
+        }
+
         let mut resolved = Vec::with_capacity(paths.len());
         for path in paths {
             let abs_path = self.fs_service.get_abs_path(path)?;
@@ -151,6 +561,100 @@ impl<'a> MarkCommand<'a> {
         Ok(resolved)
     }
 
+    /// Entries found by `--recursive` below `paths` and by `--glob`, in directory order
+    fn search_targets(&self, paths: &[String]) -> FugaResult<Vec<(String, FileInfo)>> {
+        let search = &self.args.search;
+        let mut names = GlobSetBuilder::new();
+        for name in &search.names {
+            names.add(Glob::new(name).map_err(|e| invalid_pattern(name, e))?);
+        }
+        let names = names.build().map_err(|e| invalid_pattern("--name", e))?;
+
+        let mut found = Vec::new();
+        if search.recursive {
+            let current = [".".to_string()];
+            let dirs = match paths.is_empty() {
+                true => &current[..],
+                false => paths,
+            };
+            for dir in dirs {
+                // Found entries are named from the directory, so `./` would end up in the marks
+                let abs_dir = self.clean_abs_path(dir)?;
+                let info = self.fs_service.get_file_info(&abs_dir)?;
+                if !info.exists {
+                    return Err(FugaError::FileNotFound(abs_dir));
+                }
+                if !info.is_dir {
+                    return Err(FugaError::OperationFailed(format!(
+                        "--recursive searches directories, but {abs_dir} is not one"
+                    )));
+                }
+                found.extend(self.fs_service.list_tree(&abs_dir, None)?);
+            }
+        } else {
+            for path in paths {
+                found.push(self.fs_service.get_abs_path(path)?);
+            }
+        }
+        for pattern in &search.globs {
+            found.extend(self.expand_glob(pattern)?);
+        }
+
+        let mut resolved = Vec::with_capacity(found.len());
+        for path in found {
+            let info = self.fs_service.get_file_info(&path)?;
+            if !info.exists {
+                return Err(FugaError::FileNotFound(path));
+            }
+            if search.accepts(&names, &info) {
+                resolved.push((path, info));
+            }
+        }
+        Ok(resolved)
+    }
+
+    /// Paths matching a glob, found by listing the directory its literal leading part names
+    fn expand_glob(&self, pattern: &str) -> FugaResult<Vec<String>> {
+        let abs_pattern = self.clean_abs_path(pattern)?;
+        let base: PathBuf = Path::new(&abs_pattern)
+            .components()
+            .take_while(|part| !part.as_os_str().to_string_lossy().contains(GLOB_SYNTAX))
+            .collect();
+        let base = base.to_string_lossy().into_owned();
+        if base == abs_pattern {
+            let exists = self.fs_service.get_file_info(&base)?.exists;
+            return Ok(exists.then_some(base).into_iter().collect());
+        }
+        if !self.fs_service.get_file_info(&base)?.is_dir {
+            return Ok(Vec::new());
+        }
+
+        // Without `**` a match is never deeper than the pattern itself
+        let below_base: Vec<Component> = Path::new(&abs_pattern)
+            .components()
+            .skip(Path::new(&base).components().count())
+            .collect();
+        let recursive = below_base
+            .iter()
+            .any(|part| part.as_os_str().to_string_lossy().contains("**"));
+        let depth = match recursive {
+            true => None,
+            false => Some(below_base.len()),
+        };
+
+        let matcher = GlobBuilder::new(&abs_pattern)
+            .literal_separator(true)
+            .build()
+            .map_err(|e| invalid_pattern(pattern, e))?
+            .compile_matcher();
+        Ok(self
+            .fs_service
+            .list_tree(&base, depth)?
+            .into_iter()
+            .filter(|path| matcher.is_match(path))
+            .collect())
+    }
+
     fn dedupe_preserving_order<I>(values: I) -> Vec<String>
     where
         I: IntoIterator<Item = String>,
@@ -165,12 +669,13 @@ impl<'a> MarkCommand<'a> {
         result
     }
 
-    fn target_type_from_info(info: &FileInfo) -> TargetType {
-        if info.is_file {
-            TargetType::File
-        } else {
-            TargetType::Dir
-        }
+    /// A search that found nothing leaves the mark list alone
+    fn print_nothing_found(&self) -> CommandResult {
+        println!(
+            "{} : No paths matched, the mark list is unchanged.",
+            self.ui_service.get_icon_information()
+        );
+        Ok(())
     }
 
     fn print_list_summary(&self, count: usize) -> CommandResult {
@@ -182,3 +687,143 @@ impl<'a> MarkCommand<'a> {
         Ok(())
     }
 }
+
+/// Paths from `--stdin` or `--from-file`; blank entries are skipped
+fn read_path_list(list: &PathList) -> FugaResult<Vec<String>> {
+    let data = match &list.file {
+        Some(file) => fs::read(file).map_err(|e| FugaError::from_io_error(e, file))?,
+        None => {
+            let mut data = Vec::new();
+            io::stdin().lock().read_to_end(&mut data)?;
+            data
+        }
+    };
+    parse_path_list(&data, list.nul)
+}
+
+fn parse_path_list(data: &[u8], nul: bool) -> FugaResult<Vec<String>> {
+    let separator = if nul { b'\0' } else { b'\n' };
+    data.split(|byte| *byte == separator)
+        .map(|entry| match nul {
+            true => entry,
+            // Lists written on Windows end lines with CRLF
+            false => entry.strip_suffix(b"\r").unwrap_or(entry),
+        })
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            String::from_utf8(entry.to_vec()).map_err(|_| {
+                FugaError::OperationFailed(format!(
+                    "Path list entry is not valid UTF-8: {}",
+                    String::from_utf8_lossy(entry)
+                ))
+            })
+        })
+        .collect()
+}
+
+fn type_name(kind: &TargetType) -> &'static str {
+    match kind {
+        TargetType::File => "file",
+        TargetType::Dir => "directory",
+        TargetType::Symlink => "symbolic link",
+        TargetType::Other => "special file",
+        TargetType::None => "missing path",
+    }
+}
+
+/// Characters that make an argument a glob rather than a plain path
+const GLOB_SYNTAX: [char; 4] = ['*', '?', '[', '{'];
+
+fn invalid_pattern(pattern: &str, err: globset::Error) -> FugaError {
+    FugaError::OperationFailed(format!("Invalid pattern {pattern}: {err}"))
+}
+
+/// Parse a `--larger` size such as `512`, `10K`, `1.5MiB`, or `2G`; units are powers of 1024
+pub fn parse_size(value: &str) -> Result<u64, String> {
+    let invalid =
+        || format!("invalid size '{value}', expected a number of bytes like 512, 10K, 1.5M");
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let number: f64 = number.parse().map_err(|_| invalid())?;
+    let exponent = match unit.to_ascii_lowercase().as_str() {
+        "" | "b" => 0,
+        "k" | "kb" | "kib" => 1,
+        "m" | "mb" | "mib" => 2,
+        "g" | "gb" | "gib" => 3,
+        "t" | "tb" | "tib" => 4,
+        _ => return Err(invalid()),
+    };
+    Ok((number * 1024_f64.powi(exponent)) as u64)
+}
+
+/// One-line summary of stored filters, e.g. `exclude: target, *.log; respects .gitignore`
+fn describe_filters(filters: &PathFilters) -> String {
+    let mut parts = Vec::new();
+    if !filters.exclude.is_empty() {
+        parts.push(format!("exclude: {}", filters.exclude.join(", ")));
+    }
+    if !filters.include.is_empty() {
+        parts.push(format!("include: {}", filters.include.join(", ")));
+    }
+    if filters.respect_gitignore {
+        parts.push("respects .gitignore".to_string());
+    }
+    parts.join("; ")
+}
+
+/// The `--long` columns of a mark after its type icon; unknown values show as `-`
+fn describe_record(record: &MarkRecord) -> String {
+    let time = |value: Option<u64>| value.map_or("-".to_string(), format_timestamp);
+    let mut line = format!(
+        "{:>10}  {:<23}  {:<23}  {}",
+        record
+            .size
+            .map_or("-".to_string(), |size| HumanBytes(size).to_string()),
+        time(record.mtime),
+        time(record.marked_at),
+        record.path
+    );
+    if let Some(origin) = &record.origin {
+        line.push_str(&format!("  (from {origin})"));
+    }
+    if let Some(note) = &record.note {
+        line.push_str(&format!("  # {note}"));
+    }
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_size_accepts_binary_units() {
+        assert_eq!(parse_size("512"), Ok(512));
+        assert_eq!(parse_size("10K"), Ok(10 * 1024));
+        assert_eq!(parse_size("1.5MiB"), Ok(1_572_864));
+        assert_eq!(parse_size("2gb"), Ok(2 * 1024 * 1024 * 1024));
+    }
+
+    #[test]
+    fn parse_path_list_splits_lines_or_nul_bytes() {
+        assert_eq!(
+            parse_path_list(b"a.txt\r\n\nsub dir/b.txt\n", false).unwrap(),
+            vec!["a.txt", "sub dir/b.txt"]
+        );
+        assert_eq!(
+            parse_path_list(b"line\nbreak\0c.txt\0", true).unwrap(),
+            vec!["line\nbreak", "c.txt"]
+        );
+        assert!(parse_path_list(b"\xff\n", false).is_err());
+    }
+
+    #[test]
+    fn parse_size_rejects_garbage() {
+        assert!(parse_size("").is_err());
+        assert!(parse_size("K").is_err());
+        assert!(parse_size("10Q").is_err());
+    }
+}
@@ -1,11 +1,23 @@
+pub mod batch;
 pub mod completion;
 pub mod copy;
+pub mod history;
 pub mod link;
 pub mod mark;
 pub mod r#move;
+pub mod plan;
 pub mod preset;
+pub mod sync;
+pub mod transaction;
+pub mod undo;
 
-use crate::error::FugaResult;
+use crate::commands::batch::BatchReport;
+use crate::error::{FugaError, FugaResult};
+use crate::fuga::{
+    ChecksumAlgorithm, ConflictPolicy, OperationKind, PathFilters, PreserveOptions, ReflinkMode,
+    TransferOptions, TransferOutcome,
+};
+use crate::traits::{ConfigRepository, FileSystemService, UIService};
 
 /// Trait for command execution
 pub trait Command {
@@ -15,3 +27,136 @@ pub trait Command {
 
 /// Command execution result
 pub type CommandResult = FugaResult<()>;
+
+/// Flags shared by the copy, move, and link commands
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TransferArgs {
+    /// Conflict policy override; the configured default applies when unset
+    pub on_conflict: Option<ConflictPolicy>,
+    /// Print the resolved plan instead of touching the filesystem
+    pub dry_run: bool,
+    /// Revert completed steps when a later target fails
+    pub atomic: bool,
+    /// Attempt every target even after one fails and summarize the results
+    pub keep_going: bool,
+    /// Metadata to keep on copied data
+    pub preserve: PreserveOptions,
+    /// Link with hard links, recreating marked directories
+    pub hard_link: bool,
+    /// Relative symbolic link override; the configured default applies when unset
+    pub relative_link: Option<bool>,
+    /// Whether copied files may be copy-on-write clones
+    pub reflink: ReflinkMode,
+    /// Parallel file copies override; the configured default applies when unset
+    pub jobs: Option<usize>,
+    /// Checksum copied files against their source with this hash
+    pub verify: Option<ChecksumAlgorithm>,
+    /// Continue an interrupted copy into the existing destination
+    pub resume: bool,
+    /// Entries of marked directories to leave out, on top of those stored per mark
+    pub filters: PathFilters,
+    /// Symbolic link override; copies follow links and moves keep them when unset
+    pub dereference: Option<bool>,
+}
+
+impl TransferArgs {
+    /// Merge the command-line flags with the configured defaults for `kind`
+    pub fn resolve_options(
+        &self,
+        kind: OperationKind,
+        config_repo: &dyn ConfigRepository,
+    ) -> FugaResult<TransferOptions> {
+        let user_config = config_repo.load_config()?.user_config;
+        // Resuming writes into what the earlier run left behind
+        let on_conflict = match self.resume {
+            true => ConflictPolicy::Overwrite,
+            false => self.on_conflict.unwrap_or(user_config.on_conflict),
+        };
+        let jobs = resolve_jobs(self.jobs, user_config.jobs);
+
+        Ok(TransferOptions {
+            on_conflict,
+            cleanup_on_failure: self.atomic,
+            preserve: self.preserve,
+            hard_link: self.hard_link,
+            relative_link: self.relative_link.unwrap_or(user_config.relative_links),
+            reflink: self.reflink,
+            jobs,
+            verify: self.verify,
+            resume: self.resume,
+            filters: self.filters.clone(),
+            // A move relocates a link itself, a copy duplicates what it points to
+            dereference: self.dereference.unwrap_or(kind == OperationKind::Copy),
+        })
+    }
+
+    /// Print the `--keep-going` summary and fail if any target failed
+    pub(crate) fn conclude(
+        &self,
+        report: &BatchReport,
+        kind: OperationKind,
+        ui_service: &dyn UIService,
+    ) -> CommandResult {
+        if self.keep_going {
+            report.print_summary(kind, ui_service);
+        }
+        match report.failure() {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Number of parallel file copies from the flag, then the config, where 0 means one per CPU
+pub(crate) fn resolve_jobs(flag: Option<usize>, configured: Option<usize>) -> usize {
+    match flag.or(configured).unwrap_or(1) {
+        0 => std::thread::available_parallelism().map_or(1, |count| count.get()),
+        jobs => jobs,
+    }
+}
+
+/// Turn `ask` into a concrete policy by prompting when the destination exists
+///
+/// An `--atomic` batch cannot restore an overwritten destination, so that answer is refused.
+pub(crate) fn confirm_conflict_policy(
+    fs_service: &dyn FileSystemService,
+    ui_service: &dyn UIService,
+    destination: &str,
+    options: &TransferOptions,
+) -> FugaResult<ConflictPolicy> {
+    let policy = options.on_conflict;
+    if policy != ConflictPolicy::Ask || !fs_service.get_file_info(destination)?.exists {
+        return Ok(policy);
+    }
+    match ui_service.prompt_conflict(destination)? {
+        ConflictPolicy::Overwrite if options.cleanup_on_failure => {
+            Err(atomic_overwrite_error(destination))
+        }
+        answer => Ok(answer),
+    }
+}
+
+/// Why `--atomic` refuses to overwrite an existing destination
+pub(crate) fn atomic_overwrite_error(destination: &str) -> FugaError {
+    FugaError::OperationFailed(format!(
+        "--atomic cannot restore {destination} once it is overwritten; use --on-conflict backup or rename"
+    ))
+}
+
+/// Print the skip or backup notes attached to an operation outcome
+pub(crate) fn report_conflict_resolution(ui_service: &dyn UIService, outcome: &TransferOutcome) {
+    if outcome.skipped {
+        println!(
+            "{} : Skipped {} because it already exists.",
+            ui_service.get_icon_information(),
+            ui_service.get_colorized_text(&outcome.destination, true)
+        );
+    } else if let Some(backup) = &outcome.backup {
+        println!(
+            "{} : Existing {} backed up to {}.",
+            ui_service.get_icon_information(),
+            ui_service.get_colorized_text(&outcome.destination, true),
+            ui_service.get_colorized_text(backup, true)
+        );
+    }
+}
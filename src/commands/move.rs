@@ -1,6 +1,13 @@
-use crate::commands::{Command, CommandResult};
-use crate::error::FugaError;
-use crate::fuga::TargetType;
+use crate::commands::batch::{run_batch, BatchReport};
+use crate::commands::plan::{
+    plan_transfers, print_plan, validate_plan, PlannedTransfer, Rejections,
+};
+use crate::commands::transaction::Transaction;
+use crate::commands::{
+    confirm_conflict_policy, report_conflict_resolution, Command, CommandResult, TransferArgs,
+};
+use crate::error::{FugaError, FugaResult};
+use crate::fuga::{OperationKind, PathFilters, PriorDestination, TransferOptions, TransferOutcome};
 use crate::traits::{ConfigRepository, FileSystemService, PathService, UIService};
 
 /// Move command for moving marked files/directories
@@ -10,6 +17,7 @@ pub struct MoveCommand<'a> {
     ui_service: &'a dyn UIService,
     path_service: &'a dyn PathService,
     destination: Option<String>,
+    args: TransferArgs,
 }
 
 impl<'a> MoveCommand<'a> {
@@ -19,6 +27,7 @@ impl<'a> MoveCommand<'a> {
         ui_service: &'a dyn UIService,
         path_service: &'a dyn PathService,
         destination: Option<String>,
+        args: TransferArgs,
     ) -> Self {
         Self {
             config_repo,
@@ -26,6 +35,7 @@ impl<'a> MoveCommand<'a> {
             ui_service,
             path_service,
             destination,
+            args,
         }
     }
 }
@@ -37,6 +47,9 @@ impl<'a> Command for MoveCommand<'a> {
             return Err(FugaError::OperationFailed("No targets marked.".to_string()));
         }
 
+        let options = self
+            .args
+            .resolve_options(OperationKind::Move, self.config_repo)?;
         let destination_arg = self.destination.as_deref();
         let destination_accepts_many = match destination_arg {
             Some(dest) => {
@@ -52,49 +65,164 @@ impl<'a> Command for MoveCommand<'a> {
             ));
         }
 
-        for target in &targets {
-            let info = self.fs_service.get_file_info(target)?;
-            if !info.exists {
-                return Err(FugaError::FileNotFound(target.clone()));
-            }
+        let plan = plan_transfers(
+            &targets,
+            destination_arg,
+            self.fs_service,
+            self.path_service,
+        )?;
+        if self.args.dry_run {
+            print_plan(
+                &plan,
+                OperationKind::Move,
+                &options,
+                self.fs_service,
+                self.ui_service,
+            )?;
+        }
+        let rejected = validate_plan(
+            &plan,
+            OperationKind::Move,
+            &options,
+            self.args.keep_going,
+            self.fs_service,
+        )?;
+        if self.args.dry_run {
+            return Ok(());
+        }
 
-            let target_type = if info.is_file {
-                TargetType::File
-            } else {
-                TargetType::Dir
+        let mut transaction = Transaction::new(self.fs_service, OperationKind::Move, plan.len());
+        if plan.len() > 1 {
+            self.fs_service.begin_progress(
+                &self.sources_to_copy(&plan, &rejected, &options)?,
+                options.dereference,
+            )?;
+        }
+        let result = self.run_plan(&plan, rejected, &options, &mut transaction);
+        self.fs_service.end_progress();
+        let report =
+            match transaction.finish(self.config_repo, self.ui_service, result, self.args.atomic) {
+                // A rolled back batch moved nothing, so every mark still applies
+                Err(FugaError::Cancelled { remaining }) if !self.args.atomic => {
+                    self.keep_unmoved_marked(&remaining)?;
+                    return Err(FugaError::Cancelled { remaining });
+                }
+                result => result?,
             };
+        self.keep_unmoved_marked(&report.unfinished_sources())?;
 
-            let dst_name = self.path_service.get_destination_name_with_info(
-                target,
-                &info,
-                destination_arg,
-                self.fs_service,
-            )?;
+        self.args
+            .conclude(&report, OperationKind::Move, self.ui_service)
+    }
+}
 
+impl<'a> MoveCommand<'a> {
+    /// Only targets that actually moved are unmarked; the rest can be retried
+    fn keep_unmoved_marked(&self, remaining: &[String]) -> FugaResult<()> {
+        if remaining.is_empty() {
+            self.config_repo.reset_marks()?;
             println!(
-                "{} : Moving {} {} -> {}",
-                self.ui_service.get_icon_information(),
-                self.ui_service.get_icon_for_target_type(target_type),
-                self.ui_service.get_colorized_text(target, true),
-                self.ui_service.get_colorized_text(&dst_name, true)
+                "{} : Mark list cleared after move.",
+                self.ui_service.get_icon_information()
             );
-
-            self.fs_service.move_items(target, &dst_name)?;
-
-            let dst_type = self.fs_service.get_file_type(&dst_name);
+        } else {
+            self.config_repo.set_marked_targets(remaining)?;
             println!(
-                "✅ : {} {} moved.",
-                self.ui_service.get_icon_for_target_type(dst_type),
-                self.ui_service.get_colorized_text(&dst_name, true)
+                "{} : {} target(s) that did not move remain marked.",
+                self.ui_service.get_icon_information(),
+                remaining.len()
             );
         }
+        Ok(())
+    }
+
+    /// Sources that cannot simply be renamed and will have their data copied,
+    /// with the filters the copy applies
+    fn sources_to_copy(
+        &self,
+        plan: &[PlannedTransfer],
+        rejected: &Rejections,
+        options: &TransferOptions,
+    ) -> FugaResult<Vec<(String, PathFilters)>> {
+        let mark_filters = self.config_repo.get_mark_filters()?;
+        let mut sources = Vec::new();
+        for entry in plan
+            .iter()
+            .filter(|entry| !rejected.contains_key(&entry.source))
+        {
+            let options = options.with_mark_filters(mark_filters.get(&entry.source));
+            if self
+                .fs_service
+                .move_copies(&entry.source, &entry.destination, &options)?
+            {
+                sources.push((entry.source.clone(), options.filters));
+            }
+        }
+        Ok(sources)
+    }
+
+    fn run_plan(
+        &self,
+        plan: &[PlannedTransfer],
+        rejected: Rejections,
+        options: &TransferOptions,
+        transaction: &mut Transaction,
+    ) -> FugaResult<BatchReport> {
+        let mark_filters = self.config_repo.get_mark_filters()?;
+        run_batch(plan, self.args.keep_going, rejected, |entry| {
+            let options = options.with_mark_filters(mark_filters.get(&entry.source));
+            self.move_target(entry, &options, transaction)
+        })
+    }
+
+    fn move_target(
+        &self,
+        entry: &PlannedTransfer,
+        options: &TransferOptions,
+        transaction: &mut Transaction,
+    ) -> FugaResult<TransferOutcome> {
+        self.fs_service.check_cancelled()?;
+        let target = &entry.source;
+        let dst_name = &entry.destination;
 
-        self.config_repo.reset_marks()?;
         println!(
-            "{} : Mark list cleared after move.",
-            self.ui_service.get_icon_information()
+            "{} : Moving {} {} -> {}",
+            self.ui_service.get_icon_information(),
+            self.ui_service
+                .get_icon_for_target_type(entry.target_type()),
+            self.ui_service.get_colorized_text(target, true),
+            self.ui_service.get_colorized_text(dst_name, true)
         );
 
-        Ok(())
+        let on_conflict =
+            confirm_conflict_policy(self.fs_service, self.ui_service, dst_name, options)?;
+        let outcome =
+            self.fs_service
+                .move_items(target, dst_name, &options.with_conflict(on_conflict))?;
+        transaction.record(target, &outcome)?;
+        report_conflict_resolution(self.ui_service, &outcome);
+        if outcome.skipped {
+            return Ok(outcome);
+        }
+
+        let dst_type = self.fs_service.get_file_type(&outcome.destination);
+        println!(
+            "✅ : {} {} moved ({}).",
+            self.ui_service.get_icon_for_target_type(dst_type),
+            self.ui_service
+                .get_colorized_text(&outcome.destination, true),
+            if outcome.cross_device && !options.filters.is_empty() {
+                "copied what the filters allow, then removed it from the source"
+            } else if outcome.cross_device && options.dereference {
+                "copied with links followed, then removed the source"
+            } else if matches!(outcome.prior, PriorDestination::Merged(_)) {
+                "merged into the existing directory, then removed the source"
+            } else if outcome.cross_device {
+                "copied across filesystems, then removed the source"
+            } else {
+                "renamed in place"
+            }
+        );
+        Ok(outcome)
     }
 }
@@ -0,0 +1,272 @@
+use crate::commands::atomic_overwrite_error;
+use crate::error::{FugaError, FugaResult};
+use crate::fuga::{ConflictPolicy, FileInfo, OperationKind, TargetType, TransferOptions};
+use crate::traits::{FileSystemService, PathService, UIService};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// A marked target resolved against the destination argument
+#[derive(Debug, Clone)]
+pub struct PlannedTransfer {
+    pub source: String,
+    pub info: FileInfo,
+    pub destination: String,
+    pub destination_exists: bool,
+    /// Whether the destination is a real directory, not a link to one
+    pub destination_is_dir: bool,
+}
+
+impl PlannedTransfer {
+    pub fn target_type(&self) -> TargetType {
+        self.info.target_type()
+    }
+
+    /// Whether the source is written as a directory, which merges into an existing one
+    fn writes_dir(&self, dereference: bool) -> bool {
+        self.info.is_dir && (!self.info.is_symlink || dereference)
+    }
+}
+
+/// Resolve every marked target to its destination without touching the disk
+pub fn plan_transfers(
+    targets: &[String],
+    destination_arg: Option<&str>,
+    fs_service: &dyn FileSystemService,
+    path_service: &dyn PathService,
+) -> FugaResult<Vec<PlannedTransfer>> {
+    let mut plan = Vec::with_capacity(targets.len());
+    for target in targets {
+        let mut info = fs_service.get_file_info(target)?;
+        if info.name.is_none() {
+            // Missing sources still need a name to show where they would go
+            info.name = Path::new(target)
+                .file_name()
+                .map(|value| value.to_string_lossy().into_owned());
+        }
+
+        let destination = path_service.get_destination_name_with_info(
+            target,
+            &info,
+            destination_arg,
+            fs_service,
+        )?;
+        let destination_info = fs_service.get_file_info(&destination)?;
+
+        plan.push(PlannedTransfer {
+            source: target.clone(),
+            info,
+            destination,
+            destination_exists: destination_info.exists,
+            destination_is_dir: destination_info.is_dir && !destination_info.is_symlink,
+        });
+    }
+    Ok(plan)
+}
+
+/// Print the plan for `--dry-run` without performing any operation
+pub fn print_plan(
+    plan: &[PlannedTransfer],
+    kind: OperationKind,
+    options: &TransferOptions,
+    fs_service: &dyn FileSystemService,
+    ui_service: &dyn UIService,
+) -> FugaResult<()> {
+    println!(
+        "{} : Dry run: {} target(s), nothing will be changed.",
+        ui_service.get_icon_information(),
+        plan.len()
+    );
+
+    for entry in plan {
+        let same_path = fs_service.get_abs_path(&entry.source)?
+            == fs_service.get_abs_path(&entry.destination)?;
+        println!(
+            "{} : Would {} {} {} -> {} ({})",
+            ui_service.get_icon_information(),
+            kind.verb(),
+            ui_service.get_icon_for_target_type(entry.target_type()),
+            ui_service.get_colorized_text(&entry.source, true),
+            ui_service.get_colorized_text(&entry.destination, true),
+            describe_action(entry, kind, options, same_path)
+        );
+    }
+    Ok(())
+}
+
+/// Pre-flight problems of the targets a `--keep-going` batch leaves out, by source
+pub type Rejections = HashMap<String, FugaError>;
+
+/// Check the whole batch up front and report every problem at once
+///
+/// With `keep_going` nothing is reported; the first problem of each target is
+/// returned instead, so the batch can record it and still attempt the rest.
+pub fn validate_plan(
+    plan: &[PlannedTransfer],
+    kind: OperationKind,
+    options: &TransferOptions,
+    keep_going: bool,
+    fs_service: &dyn FileSystemService,
+) -> FugaResult<Rejections> {
+    let mut problems = Vec::new();
+    let mut rejections = Rejections::new();
+    let mut claimed: HashMap<String, String> = HashMap::new();
+
+    for entry in plan {
+        let found = check_transfer(entry, kind, options, &mut claimed, fs_service)?;
+        if keep_going {
+            if let Some(problem) = found.into_iter().next() {
+                rejections.insert(entry.source.clone(), problem);
+            }
+        } else {
+            problems.extend(found);
+        }
+    }
+
+    if problems.is_empty() {
+        Ok(rejections)
+    } else {
+        Err(FugaError::PreflightFailed(problems))
+    }
+}
+
+/// Every problem that would stop a single target
+fn check_transfer(
+    entry: &PlannedTransfer,
+    kind: OperationKind,
+    options: &TransferOptions,
+    claimed: &mut HashMap<String, String>,
+    fs_service: &dyn FileSystemService,
+) -> FugaResult<Vec<FugaError>> {
+    // Hard links share data with the source, so they are checked like copies
+    let reads_source = kind != OperationKind::Link || options.hard_link;
+    let mut problems = Vec::new();
+
+    if !entry.info.exists {
+        problems.push(FugaError::FileNotFound(entry.source.clone()));
+        return Ok(problems);
+    }
+
+    let abs_src = fs_service.get_abs_path(&entry.source)?;
+    let abs_dst = fs_service.get_abs_path(&entry.destination)?;
+    if abs_src == abs_dst {
+        problems.push(FugaError::DuplicatePath {
+            source: abs_src,
+            destination: abs_dst,
+        });
+        return Ok(problems);
+    }
+
+    // Without dereferencing, a link is copied as it is, even when dangling
+    let follows_link = !entry.info.is_symlink || options.dereference;
+    if reads_source && follows_link {
+        // A rename moves these fine, but there is no data to copy
+        if kind == OperationKind::Copy && entry.target_type() == TargetType::Other {
+            problems.push(FugaError::OperationFailed(format!(
+                "Cannot copy {abs_src}: only files, directories, and symbolic links can be copied"
+            )));
+            return Ok(problems);
+        }
+        if let Err(err) = fs_service.check_readable(&abs_src) {
+            problems.push(err);
+        }
+        if entry.info.is_dir && Path::new(&abs_dst).starts_with(&abs_src) {
+            problems.push(FugaError::OperationFailed(format!(
+                "Cannot {} {abs_src} into itself ({abs_dst})",
+                kind.verb()
+            )));
+        }
+    }
+
+    if let Some(previous) = claimed.insert(abs_dst.clone(), abs_src.clone()) {
+        problems.push(FugaError::OperationFailed(format!(
+            "{previous} and {abs_src} would both be written to {abs_dst}"
+        )));
+    }
+
+    if entry.destination_exists && options.on_conflict == ConflictPolicy::Fail {
+        problems.push(FugaError::DestinationExists(entry.destination.clone()));
+    }
+    // Directories merge into directories, but nothing replaces one with the other
+    if entry.destination_exists
+        && options.on_conflict == ConflictPolicy::Overwrite
+        && kind != OperationKind::Link
+        && entry.writes_dir(options.dereference) != entry.destination_is_dir
+    {
+        let what = if entry.destination_is_dir {
+            "a directory with a file"
+        } else {
+            "a file with a directory"
+        };
+        problems.push(FugaError::OperationFailed(format!(
+            "Cannot overwrite {what}: {}",
+            entry.destination
+        )));
+    }
+    // Only `--atomic` cleans up after failures, and a rollback cannot bring back what was overwritten
+    if entry.destination_exists
+        && options.cleanup_on_failure
+        && options.on_conflict == ConflictPolicy::Overwrite
+    {
+        problems.push(atomic_overwrite_error(&entry.destination));
+    }
+
+    if let Some(parent) = parent_dir(&abs_dst) {
+        if let Err(err) = fs_service.check_writable_dir(&parent) {
+            problems.push(err);
+        } else if options.hard_link {
+            if let Err(err) = fs_service.check_same_filesystem(&abs_src, &parent) {
+                problems.push(err);
+            }
+        }
+    }
+
+    if kind == OperationKind::Move {
+        // Moving removes the source entry from its parent directory
+        if let Some(parent) = parent_dir(&abs_src) {
+            if let Err(err) = fs_service.check_writable_dir(&parent) {
+                problems.push(err);
+            }
+        }
+    }
+
+    Ok(problems)
+}
+
+fn parent_dir(path: &str) -> Option<String> {
+    Path::new(path)
+        .parent()
+        .map(|parent| parent.to_string_lossy().into_owned())
+}
+
+fn describe_action(
+    entry: &PlannedTransfer,
+    kind: OperationKind,
+    options: &TransferOptions,
+    same_path: bool,
+) -> &'static str {
+    if !entry.info.exists {
+        return "source missing, would fail";
+    }
+    if same_path {
+        return "source and destination are the same, would fail";
+    }
+    if !entry.destination_exists {
+        return "destination is free";
+    }
+
+    match options.on_conflict {
+        ConflictPolicy::Overwrite
+            if kind != OperationKind::Link
+                && entry.destination_is_dir
+                && entry.writes_dir(options.dereference) =>
+        {
+            "destination directory exists, would merge into it"
+        }
+        ConflictPolicy::Overwrite => "destination exists, would overwrite",
+        ConflictPolicy::Skip => "destination exists, would skip",
+        ConflictPolicy::Rename => "destination exists, would write to a free name",
+        ConflictPolicy::Backup => "destination exists, would back it up first",
+        ConflictPolicy::Fail => "destination exists, would fail",
+        ConflictPolicy::Ask => "destination exists, would ask",
+    }
+}
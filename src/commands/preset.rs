@@ -130,9 +130,12 @@ impl<'a> Command for PresetCommand<'a> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::config::AppConfig;
+    use crate::config::{AppConfig, JournalEntry, MarkRecord, UnfinishedCopy};
     use crate::error::FugaResult;
-    use crate::fuga::TargetType;
+    use crate::fuga::{
+        ConflictPolicy, PathFilters, SyncOptions, SyncSummary, TargetType, TransferOptions,
+        TransferOutcome,
+    };
     use std::cell::RefCell;
     use std::collections::BTreeMap;
 
@@ -184,6 +187,20 @@ mod tests {
             Ok(self.marks.borrow().clone())
         }
 
+        fn get_mark_records(&self) -> FugaResult<Vec<MarkRecord>> {
+            Ok(self
+                .marks
+                .borrow()
+                .iter()
+                .map(|path| MarkRecord::bare(path))
+                .collect())
+        }
+
+        fn set_mark_records(&self, records: &[MarkRecord]) -> FugaResult<()> {
+            *self.marks.borrow_mut() = records.iter().map(|record| record.path.clone()).collect();
+            Ok(())
+        }
+
         fn reset_marks(&self) -> FugaResult<()> {
             self.marks.borrow_mut().clear();
             Ok(())
@@ -207,6 +224,34 @@ mod tests {
         fn delete_preset(&self, name: &str) -> FugaResult<bool> {
             Ok(self.presets.borrow_mut().remove(name).is_some())
         }
+
+        fn get_journal(&self) -> FugaResult<Vec<JournalEntry>> {
+            Ok(Vec::new())
+        }
+
+        fn set_journal(&self, _entries: &[JournalEntry]) -> FugaResult<()> {
+            Ok(())
+        }
+
+        fn append_journal(&self, _entry: &JournalEntry) -> FugaResult<()> {
+            Ok(())
+        }
+
+        fn get_unfinished_copy(&self) -> FugaResult<Option<UnfinishedCopy>> {
+            Ok(None)
+        }
+
+        fn set_unfinished_copy(&self, _copy: Option<&UnfinishedCopy>) -> FugaResult<()> {
+            Ok(())
+        }
+
+        fn get_mark_filters(&self) -> FugaResult<BTreeMap<String, PathFilters>> {
+            Ok(BTreeMap::new())
+        }
+
+        fn set_mark_filters(&self, _path: &str, _filters: &PathFilters) -> FugaResult<()> {
+            Ok(())
+        }
     }
 
     #[derive(Default)]
@@ -225,15 +270,97 @@ mod tests {
             TargetType::File
         }
 
-        fn copy_items(&self, _src: &str, _dst: &str) -> FugaResult<()> {
+        fn list_tree(&self, _dir: &str, _depth: Option<usize>) -> FugaResult<Vec<String>> {
+            Ok(Vec::new())
+        }
+
+        fn check_readable(&self, _path: &str) -> FugaResult<()> {
+            unimplemented!()
+        }
+
+        fn check_writable_dir(&self, _path: &str) -> FugaResult<()> {
+            unimplemented!()
+        }
+
+        fn check_same_filesystem(&self, _path: &str, _dir: &str) -> FugaResult<()> {
             unimplemented!()
         }
 
-        fn move_items(&self, _src: &str, _dst: &str) -> FugaResult<()> {
+        fn check_cancelled(&self) -> FugaResult<()> {
             unimplemented!()
         }
 
-        fn link_items(&self, _src: &str, _dst: &str) -> FugaResult<()> {
+        fn begin_progress(
+            &self,
+            _sources: &[(String, PathFilters)],
+            _dereference: bool,
+        ) -> FugaResult<()> {
+            unimplemented!()
+        }
+
+        fn end_progress(&self) {
+            unimplemented!()
+        }
+
+        fn remove_items(&self, _path: &str) -> FugaResult<()> {
+            unimplemented!()
+        }
+
+        fn create_dir(&self, _path: &str) -> FugaResult<()> {
+            unimplemented!()
+        }
+
+        fn copy_items(
+            &self,
+            _src: &str,
+            _dst: &str,
+            _options: &TransferOptions,
+        ) -> FugaResult<TransferOutcome> {
+            unimplemented!()
+        }
+
+        fn verify_copy(
+            &self,
+            _src: &str,
+            _dst: &str,
+            _options: &TransferOptions,
+        ) -> FugaResult<Vec<String>> {
+            unimplemented!()
+        }
+
+        fn move_items(
+            &self,
+            _src: &str,
+            _dst: &str,
+            _options: &TransferOptions,
+        ) -> FugaResult<TransferOutcome> {
+            unimplemented!()
+        }
+
+        fn move_copies(
+            &self,
+            _src: &str,
+            _dst: &str,
+            _options: &TransferOptions,
+        ) -> FugaResult<bool> {
+            unimplemented!()
+        }
+
+        fn link_items(
+            &self,
+            _src: &str,
+            _dst: &str,
+            _options: &TransferOptions,
+        ) -> FugaResult<TransferOutcome> {
+            unimplemented!()
+        }
+
+        fn sync_items(
+            &self,
+            _src: &str,
+            _dst: &str,
+            _options: &SyncOptions,
+        ) -> FugaResult<SyncSummary> {
             unimplemented!()
         }
     }
@@ -253,6 +380,10 @@ mod tests {
         fn get_icon_for_target_type(&self, _target_type: TargetType) -> String {
             "[FILE]".to_string()
         }
+
+        fn prompt_conflict(&self, _destination: &str) -> FugaResult<ConflictPolicy> {
+            unimplemented!("not required for preset tests")
+        }
     }
 
     fn make_command<'a>(
@@ -0,0 +1,180 @@
+use crate::commands::plan::{plan_transfers, validate_plan, PlannedTransfer};
+use crate::commands::{resolve_jobs, Command, CommandResult};
+use crate::error::{FugaError, FugaResult};
+use crate::fuga::{
+    ChecksumAlgorithm, ConflictPolicy, OperationKind, PathFilters, ReflinkMode, SyncChangeKind,
+    SyncOptions, SyncSummary, TransferOptions,
+};
+use crate::traits::{ConfigRepository, FileSystemService, PathService, UIService};
+
+/// Flags of the sync command
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SyncArgs {
+    /// Compare contents with this hash instead of size and modification time
+    pub checksum: Option<ChecksumAlgorithm>,
+    /// Remove destination entries that are not in the source
+    pub delete: bool,
+    /// Print the changes instead of making them
+    pub dry_run: bool,
+    /// Parallel file copies override; the configured default applies when unset
+    pub jobs: Option<usize>,
+    /// Copy what symbolic links point to instead of mirroring the links
+    pub dereference: bool,
+    /// Entries of marked directories to leave out, on top of those stored per mark
+    pub filters: PathFilters,
+}
+
+/// Sync command for mirroring marked files/directories into a destination
+pub struct SyncCommand<'a> {
+    config_repo: &'a dyn ConfigRepository,
+    fs_service: &'a dyn FileSystemService,
+    ui_service: &'a dyn UIService,
+    path_service: &'a dyn PathService,
+    destination: Option<String>,
+    args: SyncArgs,
+}
+
+impl<'a> SyncCommand<'a> {
+    pub fn new(
+        config_repo: &'a dyn ConfigRepository,
+        fs_service: &'a dyn FileSystemService,
+        ui_service: &'a dyn UIService,
+        path_service: &'a dyn PathService,
+        destination: Option<String>,
+        args: SyncArgs,
+    ) -> Self {
+        Self {
+            config_repo,
+            fs_service,
+            ui_service,
+            path_service,
+            destination,
+            args,
+        }
+    }
+}
+
+impl<'a> Command for SyncCommand<'a> {
+    fn execute(&self) -> CommandResult {
+        let targets = self.config_repo.get_marked_targets()?;
+        if targets.is_empty() {
+            return Err(FugaError::OperationFailed("No targets marked.".to_string()));
+        }
+
+        let destination_arg = self.destination.as_deref();
+        let destination_accepts_many = match destination_arg {
+            Some(dest) => {
+                let info = self.fs_service.get_file_info(dest)?;
+                info.exists && info.is_dir
+            }
+            None => false,
+        };
+        if targets.len() > 1 && destination_arg.is_some() && !destination_accepts_many {
+            return Err(FugaError::OperationFailed(
+                "Cannot sync multiple items to a single file path.".to_string(),
+            ));
+        }
+
+        let plan = plan_transfers(
+            &targets,
+            destination_arg,
+            self.fs_service,
+            self.path_service,
+        )?;
+        // Existing destinations are what a sync updates, even with another type, so only
+        // the sources need checking and no conflict policy applies. Dangling links are
+        // mirrored whatever the setting, so only live ones are followed to be read.
+        let (dangling, live): (Vec<_>, Vec<_>) = plan
+            .iter()
+            .cloned()
+            .partition(|entry| entry.info.is_symlink && !entry.info.is_file && !entry.info.is_dir);
+        for (entries, dereference) in [(live, self.args.dereference), (dangling, false)] {
+            let checks = TransferOptions {
+                on_conflict: ConflictPolicy::Skip,
+                dereference,
+                ..TransferOptions::default()
+            };
+            validate_plan(
+                &entries,
+                OperationKind::Copy,
+                &checks,
+                false,
+                self.fs_service,
+            )?;
+        }
+
+        let user_config = self.config_repo.load_config()?.user_config;
+        let options = SyncOptions {
+            checksum: self.args.checksum,
+            delete: self.args.delete,
+            dry_run: self.args.dry_run,
+            reflink: ReflinkMode::Auto,
+            jobs: resolve_jobs(self.args.jobs, user_config.jobs),
+            filters: self.args.filters.clone(),
+            dereference: self.args.dereference,
+        };
+        if options.dry_run {
+            println!(
+                "{} : Dry run: {} target(s), nothing will be changed.",
+                self.ui_service.get_icon_information(),
+                plan.len()
+            );
+        }
+
+        let mark_filters = self.config_repo.get_mark_filters()?;
+        for entry in &plan {
+            let options = options.with_mark_filters(mark_filters.get(&entry.source));
+            self.sync_target(entry, &options)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> SyncCommand<'a> {
+    fn sync_target(&self, entry: &PlannedTransfer, options: &SyncOptions) -> FugaResult<()> {
+        self.fs_service.check_cancelled()?;
+        println!(
+            "{} : Syncing {} {} -> {}",
+            self.ui_service.get_icon_information(),
+            self.ui_service
+                .get_icon_for_target_type(entry.target_type()),
+            self.ui_service.get_colorized_text(&entry.source, true),
+            self.ui_service.get_colorized_text(&entry.destination, true)
+        );
+
+        let summary = self
+            .fs_service
+            .sync_items(&entry.source, &entry.destination, options)?;
+        for change in &summary.changes {
+            let sign = match change.kind {
+                SyncChangeKind::Added => '+',
+                SyncChangeKind::Updated => '~',
+                SyncChangeKind::Deleted => '-',
+            };
+            println!("  {sign} {}", change.path);
+        }
+
+        let verb = if options.dry_run {
+            "would be synced"
+        } else {
+            "synced"
+        };
+        println!(
+            "✅ : {} {}: {}",
+            self.ui_service.get_colorized_text(&entry.destination, true),
+            verb,
+            describe(&summary)
+        );
+        Ok(())
+    }
+}
+
+fn describe(summary: &SyncSummary) -> String {
+    format!(
+        "{} added, {} updated, {} deleted, {} unchanged.",
+        summary.count(SyncChangeKind::Added),
+        summary.count(SyncChangeKind::Updated),
+        summary.count(SyncChangeKind::Deleted),
+        summary.unchanged
+    )
+}
@@ -0,0 +1,238 @@
+use crate::commands::batch::BatchReport;
+use crate::config::JournalEntry;
+use crate::error::{FugaError, FugaResult};
+use crate::fuga::{
+    ConflictPolicy, OperationKind, OperationStep, PathFilters, PreserveOptions, PriorDestination,
+    ReflinkMode, TargetType, TransferOptions, TransferOutcome,
+};
+use crate::traits::{ConfigRepository, FileSystemService, UIService};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Records the completed steps of a batch so it can be reverted
+pub struct Transaction<'a> {
+    fs_service: &'a dyn FileSystemService,
+    kind: OperationKind,
+    targets: usize,
+    steps: Vec<OperationStep>,
+}
+
+impl<'a> Transaction<'a> {
+    pub fn new(fs_service: &'a dyn FileSystemService, kind: OperationKind, targets: usize) -> Self {
+        Self {
+            fs_service,
+            kind,
+            targets,
+            steps: Vec::new(),
+        }
+    }
+
+    /// Remember a finished operation; skipped targets leave nothing to revert
+    pub fn record(&mut self, source: &str, outcome: &TransferOutcome) -> FugaResult<()> {
+        if outcome.skipped {
+            return Ok(());
+        }
+
+        let backup = match &outcome.backup {
+            Some(backup) => Some(self.fs_service.get_abs_path(backup)?),
+            None => None,
+        };
+        self.steps.push(OperationStep {
+            kind: self.kind,
+            source: self.fs_service.get_abs_path(source)?,
+            destination: self.fs_service.get_abs_path(&outcome.destination)?,
+            backup,
+            prior: outcome.prior.clone(),
+        });
+        Ok(())
+    }
+
+    /// Journal the outcome, rolling back first when an atomic batch failed
+    pub fn finish(
+        mut self,
+        config_repo: &dyn ConfigRepository,
+        ui_service: &dyn UIService,
+        result: FugaResult<BatchReport>,
+        atomic: bool,
+    ) -> FugaResult<BatchReport> {
+        let err = match result {
+            Ok(report) => {
+                let error = report.failure().map(|err| err.to_string());
+                self.commit(config_repo, error)?;
+                return Ok(report);
+            }
+            Err(err) if atomic => self.rollback(ui_service, err),
+            Err(err) => err,
+        };
+
+        // Failures are journaled too so partial work can be undone and reviewed
+        if let Err(journal_err) = self.commit(config_repo, Some(err.to_string())) {
+            eprintln!("❌ : Failed to record the operation journal: {journal_err}");
+        }
+        Err(err)
+    }
+
+    fn commit(self, config_repo: &dyn ConfigRepository, error: Option<String>) -> FugaResult<()> {
+        config_repo.append_journal(&JournalEntry {
+            kind: self.kind,
+            timestamp: unix_timestamp(),
+            targets: self.targets,
+            steps: self.steps,
+            error,
+            undone: false,
+        })
+    }
+
+    /// Revert every recorded step newest-first and return the error to report
+    ///
+    /// Steps that could not be reverted stay recorded so they remain undoable.
+    fn rollback(&mut self, ui_service: &dyn UIService, cause: FugaError) -> FugaError {
+        println!(
+            "{} : {} failed, rolling back {} completed step(s).",
+            ui_service.get_icon_information(),
+            capitalize(self.kind.verb()),
+            self.steps.len()
+        );
+
+        let mut problems = Vec::new();
+        let mut remaining = Vec::new();
+        for step in self.steps.drain(..).rev() {
+            if let Err(err) = revert_step(&step, self.fs_service) {
+                problems.push(err);
+                remaining.push(step);
+            }
+        }
+        remaining.reverse();
+        self.steps = remaining;
+
+        if problems.is_empty() {
+            println!("✅ : Rollback complete, nothing was changed.");
+            cause
+        } else {
+            FugaError::RollbackFailed {
+                cause: Box::new(cause),
+                problems,
+            }
+        }
+    }
+}
+
+/// Fail when reverting the step could not bring back what its destination held before
+pub fn check_revertible(step: &OperationStep) -> FugaResult<()> {
+    let reason = match (&step.prior, step.kind) {
+        (PriorDestination::Vacant, _) => return Ok(()),
+        (PriorDestination::Merged(_), _) => return Ok(()),
+        (PriorDestination::Overwritten, _) => "it replaced what was there before",
+    };
+    Err(FugaError::OperationFailed(format!(
+        "Cannot undo the {} to {}: {reason}",
+        step.kind.verb(),
+        step.destination
+    )))
+}
+
+/// Reverse a single completed step, restoring any backup it displaced
+///
+/// A copy merged into an existing directory only loses the entries it created, and
+/// a move puts them back next to whatever stayed behind in its source.
+pub fn revert_step(step: &OperationStep, fs_service: &dyn FileSystemService) -> FugaResult<()> {
+    check_revertible(step)?;
+
+    // Never clobber something that appeared in the meantime
+    let options = TransferOptions {
+        on_conflict: ConflictPolicy::Fail,
+        cleanup_on_failure: false,
+        // Moving back across filesystems should not change what the item looked like
+        preserve: PreserveOptions {
+            mode: true,
+            timestamps: true,
+            ..PreserveOptions::default()
+        },
+        hard_link: false,
+        relative_link: false,
+        reflink: ReflinkMode::Auto,
+        jobs: 1,
+        verify: None,
+        resume: false,
+        filters: PathFilters::default(),
+        // Links were moved as links, so they go back the same way
+        dereference: false,
+    };
+
+    match step.kind {
+        OperationKind::Move => {
+            let moved = match &step.prior {
+                PriorDestination::Merged(created) => created.as_slice(),
+                _ => std::slice::from_ref(&step.destination),
+            };
+            for path in moved.iter().rev() {
+                let target = match Path::new(path).strip_prefix(&step.destination) {
+                    Ok(relative) if !relative.as_os_str().is_empty() => {
+                        Path::new(&step.source).join(relative)
+                    }
+                    _ => Path::new(&step.source).to_path_buf(),
+                };
+                merge_back(path, &target.to_string_lossy(), fs_service, &options)?;
+            }
+        }
+        OperationKind::Copy | OperationKind::Link => match &step.prior {
+            PriorDestination::Merged(created) => {
+                for path in created.iter().rev() {
+                    // Already gone is as good as removed
+                    if fs_service.get_file_type(path) != TargetType::None {
+                        fs_service.remove_items(path)?;
+                    }
+                }
+            }
+            _ => fs_service.remove_items(&step.destination)?,
+        },
+    }
+
+    if let Some(backup) = &step.backup {
+        fs_service.move_items(backup, &step.destination, &options)?;
+    }
+    Ok(())
+}
+
+/// Move `from` back to `to`, merging into a directory a filtered move left behind
+fn merge_back(
+    from: &str,
+    to: &str,
+    fs_service: &dyn FileSystemService,
+    options: &TransferOptions,
+) -> FugaResult<()> {
+    match (fs_service.get_file_type(from), fs_service.get_file_type(to)) {
+        (_, TargetType::None) => {
+            if let Some(parent) = Path::new(to).parent() {
+                fs_service.create_dir(&parent.to_string_lossy())?;
+            }
+            fs_service.move_items(from, to, options)?;
+        }
+        (TargetType::Dir, TargetType::Dir) => {
+            for child in fs_service.list_tree(from, Some(1))? {
+                let name = Path::new(&child).file_name().unwrap_or_default();
+                let target = Path::new(to).join(name);
+                merge_back(&child, &target.to_string_lossy(), fs_service, options)?;
+            }
+            // Everything inside went back, so only the emptied directory is left
+            fs_service.remove_items(from)?;
+        }
+        _ => return Err(FugaError::DestinationExists(to.to_string())),
+    }
+    Ok(())
+}
+
+pub(crate) fn unix_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default()
+}
+
+pub(crate) fn capitalize(value: &str) -> String {
+    let mut chars = value.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
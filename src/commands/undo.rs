@@ -0,0 +1,91 @@
+use crate::commands::transaction::{check_revertible, revert_step};
+use crate::commands::{Command, CommandResult};
+use crate::fuga::OperationKind;
+use crate::traits::{ConfigRepository, FileSystemService, UIService};
+
+/// Undo command for reverting the most recent copy, move, or link
+pub struct UndoCommand<'a> {
+    config_repo: &'a dyn ConfigRepository,
+    fs_service: &'a dyn FileSystemService,
+    ui_service: &'a dyn UIService,
+}
+
+impl<'a> UndoCommand<'a> {
+    pub fn new(
+        config_repo: &'a dyn ConfigRepository,
+        fs_service: &'a dyn FileSystemService,
+        ui_service: &'a dyn UIService,
+    ) -> Self {
+        Self {
+            config_repo,
+            fs_service,
+            ui_service,
+        }
+    }
+}
+
+impl<'a> Command for UndoCommand<'a> {
+    fn execute(&self) -> CommandResult {
+        let mut journal = self.config_repo.get_journal()?;
+        let Some(index) = journal
+            .iter()
+            .rposition(|entry| !entry.undone && !entry.steps.is_empty())
+        else {
+            println!(
+                "{} : Nothing to undo.",
+                self.ui_service.get_icon_information()
+            );
+            return Ok(());
+        };
+
+        // Refuse before touching anything rather than stop halfway
+        for step in &journal[index].steps {
+            check_revertible(step)?;
+        }
+
+        let kind = journal[index].kind;
+        println!(
+            "{} : Undoing {} of {} target(s).",
+            self.ui_service.get_icon_information(),
+            kind.verb(),
+            journal[index].steps.len()
+        );
+
+        let steps = journal[index].steps.clone();
+        for (position, step) in steps.iter().enumerate().rev() {
+            let action = match kind {
+                OperationKind::Move => "Moving back",
+                OperationKind::Copy => "Removing copy",
+                OperationKind::Link => "Removing link",
+            };
+            println!(
+                "{} : {} {} -> {}",
+                self.ui_service.get_icon_information(),
+                action,
+                self.ui_service.get_colorized_text(&step.destination, true),
+                self.ui_service.get_colorized_text(&step.source, true)
+            );
+
+            if let Err(err) = revert_step(step, self.fs_service) {
+                // Keep only the steps still in place so a later undo can resume
+                journal[index].steps.truncate(position + 1);
+                self.config_repo.set_journal(&journal)?;
+                return Err(err);
+            }
+
+            if let Some(backup) = &step.backup {
+                println!(
+                    "{} : Restored {} from {}.",
+                    self.ui_service.get_icon_information(),
+                    self.ui_service.get_colorized_text(&step.destination, true),
+                    self.ui_service.get_colorized_text(backup, true)
+                );
+            }
+        }
+
+        journal[index].undone = true;
+        self.config_repo.set_journal(&journal)?;
+        println!("✅ : Last {} undone.", kind.verb());
+        Ok(())
+    }
+}
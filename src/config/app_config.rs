@@ -1,3 +1,6 @@
+use crate::fuga::{
+    ConflictPolicy, FileInfo, OperationKind, OperationStep, PathFilters, TargetType,
+};
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 
@@ -12,16 +15,135 @@ pub struct AppConfig {
 #[derive(Default, Serialize, Deserialize, Debug)]
 pub struct UserConfig {
     pub box_path: String,
+
+    /// Conflict policy used when `--on-conflict` is not given
+    #[serde(default)]
+    pub on_conflict: ConflictPolicy,
+
+    /// Make `fuga link` create relative symbolic links unless `--absolute` is given
+    #[serde(default)]
+    pub relative_links: bool,
+
+    /// Files copied in parallel when `--jobs` is not given; 0 uses one per CPU
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jobs: Option<usize>,
 }
 
 #[derive(Default, Serialize, Deserialize, Debug)]
 pub struct Data {
-    #[serde(default)]
+    /// Marked paths as written before mark records, migrated to `marks` on first read
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub targets: Vec<String>,
 
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub target: Option<String>,
 
+    #[serde(default)]
+    pub marks: Vec<MarkRecord>,
+
     #[serde(default)]
     pub presets: BTreeMap<String, Vec<String>>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unfinished_copy: Option<UnfinishedCopy>,
+
+    /// Filters stored with `fuga mark`, keyed by marked path
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub mark_filters: BTreeMap<String, PathFilters>,
+}
+
+/// Layout version written with every [`MarkRecord`]
+pub const MARK_RECORD_VERSION: u32 = 1;
+
+/// A marked path and what it looked like when it was marked.
+///
+/// Marks migrated from plain paths only know their path.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct MarkRecord {
+    pub version: u32,
+    pub path: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<TargetType>,
+    /// Size in bytes of what the path pointed to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+    /// Modification time in seconds since the Unix epoch
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mtime: Option<u64>,
+    /// Seconds since the Unix epoch
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub marked_at: Option<u64>,
+    /// Working directory `fuga mark` ran in
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub origin: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+impl MarkRecord {
+    /// Record for a path marked without details, e.g. from a preset or the dashboard
+    pub fn bare(path: &str) -> Self {
+        Self {
+            version: MARK_RECORD_VERSION,
+            path: path.to_string(),
+            kind: None,
+            size: None,
+            mtime: None,
+            marked_at: None,
+            origin: None,
+            note: None,
+        }
+    }
+
+    /// Record for `path` as described by `info`, marked now from `origin`
+    pub fn new(path: &str, info: &FileInfo, marked_at: u64, origin: Option<String>) -> Self {
+        Self {
+            kind: Some(info.target_type()),
+            size: Some(info.size),
+            mtime: info.modified,
+            marked_at: Some(marked_at),
+            origin,
+            ..Self::bare(path)
+        }
+    }
+}
+
+/// The operation journal, stored in its own file next to the config
+///
+/// Merged steps list every path they created, so the journal can grow far larger
+/// than the config, which is read on every invocation.
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct Journal {
+    #[serde(default)]
+    pub entries: Vec<JournalEntry>,
+}
+
+/// One copy, move, or link invocation recorded for `fuga undo` and `fuga history`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct JournalEntry {
+    pub kind: OperationKind,
+    /// Seconds since the Unix epoch
+    pub timestamp: u64,
+    /// Number of marked targets the invocation was asked to process
+    #[serde(default)]
+    pub targets: usize,
+    /// Steps that completed and are still in place
+    #[serde(default)]
+    pub steps: Vec<OperationStep>,
+    /// Error that stopped the invocation, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(default)]
+    pub undone: bool,
+}
+
+/// A copy that started but has not finished, kept so the next copy can offer `--resume`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct UnfinishedCopy {
+    /// Marked targets the copy was started with
+    pub targets: Vec<String>,
+    /// Absolute destination the targets were copied to
+    pub destination: String,
+    /// Seconds since the Unix epoch
+    pub timestamp: u64,
 }
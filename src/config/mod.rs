@@ -1,5 +1,5 @@
 pub mod app_config;
 pub mod storage;
 
-pub use app_config::AppConfig;
+pub use app_config::{AppConfig, Journal, JournalEntry, MarkRecord, UnfinishedCopy};
 pub use storage::FileConfigRepository;
@@ -1,10 +1,17 @@
-use crate::config::AppConfig;
+use crate::config::{AppConfig, Journal, JournalEntry, MarkRecord, UnfinishedCopy};
 use crate::error::{FugaError, FugaResult};
-use crate::fuga::APP_NAME;
+use crate::fuga::{PathFilters, APP_NAME};
 use crate::traits::ConfigRepository;
 use dirs::config_dir;
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 
+/// Number of journal entries kept for undo and history
+const JOURNAL_LIMIT: usize = 200;
+
+/// Name of the journal file, which sits next to the config file
+const JOURNAL_FILE_NAME: &str = "journal.toml";
+
 #[derive(Default)]
 pub struct FileConfigRepository;
 
@@ -16,6 +23,11 @@ impl FileConfigRepository {
     fn get_config_path() -> Option<PathBuf> {
         config_dir()
     }
+
+    fn get_journal_path() -> FugaResult<PathBuf> {
+        let config_file = confy::get_configuration_file_path(APP_NAME, APP_NAME)?;
+        Ok(config_file.with_file_name(JOURNAL_FILE_NAME))
+    }
 }
 
 impl ConfigRepository for FileConfigRepository {
@@ -38,56 +50,71 @@ impl ConfigRepository for FileConfigRepository {
     }
 
     fn set_marked_targets(&self, targets: &[String]) -> FugaResult<()> {
-        let mut config = self.load_config()?;
-        config.data.targets = targets.to_vec();
-        config.data.target = None;
-        self.store_config(&config)
+        // Marks that stay keep what was recorded about them
+        let mut existing = self.get_mark_records()?;
+        let records: Vec<MarkRecord> = targets
+            .iter()
+            .map(
+                |path| match existing.iter().position(|record| &record.path == path) {
+                    Some(index) => existing.swap_remove(index),
+                    None => MarkRecord::bare(path),
+                },
+            )
+            .collect();
+        self.set_mark_records(&records)
     }
 
     fn get_marked_targets(&self) -> FugaResult<Vec<String>> {
+        let records = self.get_mark_records()?;
+        Ok(records.into_iter().map(|record| record.path).collect())
+    }
+
+    fn get_mark_records(&self) -> FugaResult<Vec<MarkRecord>> {
         let mut config = self.load_config()?;
-        let mut mutated = false;
 
-        // Remove empty strings that may be left over from legacy state
-        if config
-            .data
-            .targets
-            .iter()
-            .any(|value| value.trim().is_empty())
-        {
-            config.data.targets.retain(|value| !value.trim().is_empty());
-            mutated = true;
+        // Plain paths of older releases become records; the single legacy
+        // target only counts when there is no list
+        let legacy = config.data.target.take();
+        let mut mutated = legacy.is_some() || !config.data.targets.is_empty();
+        let mut plain = std::mem::take(&mut config.data.targets);
+        if plain.is_empty() {
+            plain.extend(legacy);
         }
-
-        if !config.data.targets.is_empty() {
-            if config.data.target.is_some() {
-                config.data.target = None;
-                mutated = true;
-            }
-
-            if mutated {
-                self.store_config(&config)?;
-            }
-
-            return Ok(config.data.targets.clone());
+        if config.data.marks.is_empty() {
+            config.data.marks = plain.iter().map(|path| MarkRecord::bare(path)).collect();
         }
 
-        let legacy = config
+        // Remove empty paths that may be left over from legacy state
+        if config
             .data
-            .target
-            .take()
-            .filter(|legacy| !legacy.trim().is_empty());
-
-        if let Some(legacy_target) = legacy {
-            config.data.targets = vec![legacy_target];
+            .marks
+            .iter()
+            .any(|record| record.path.trim().is_empty())
+        {
+            config
+                .data
+                .marks
+                .retain(|record| !record.path.trim().is_empty());
             mutated = true;
         }
 
         if mutated {
             self.store_config(&config)?;
         }
+        Ok(config.data.marks)
+    }
 
-        Ok(config.data.targets.clone())
+    fn set_mark_records(&self, records: &[MarkRecord]) -> FugaResult<()> {
+        let mut config = self.load_config()?;
+        config.data.marks = records.to_vec();
+        config.data.targets.clear();
+        config.data.target = None;
+        // Filters only live as long as their mark
+        config
+            .data
+            .mark_filters
+            .retain(|path, _| records.iter().any(|record| &record.path == path));
+        self.store_config(&config)
     }
 
     fn reset_marks(&self) -> FugaResult<()> {
@@ -121,4 +148,58 @@ impl ConfigRepository for FileConfigRepository {
         }
         Ok(existed)
     }
+
+    fn get_journal(&self) -> FugaResult<Vec<JournalEntry>> {
+        let path = Self::get_journal_path()?;
+        // Reading must not leave an empty journal file behind
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        Ok(confy::load_path::<Journal>(path)?.entries)
+    }
+
+    fn set_journal(&self, entries: &[JournalEntry]) -> FugaResult<()> {
+        let journal = Journal {
+            entries: entries.to_vec(),
+        };
+        confy::store_path(Self::get_journal_path()?, journal)?;
+        Ok(())
+    }
+
+    fn append_journal(&self, entry: &JournalEntry) -> FugaResult<()> {
+        let mut entries = self.get_journal()?;
+        entries.push(entry.clone());
+        let overflow = entries.len().saturating_sub(JOURNAL_LIMIT);
+        entries.drain(..overflow);
+        self.set_journal(&entries)
+    }
+
+    fn get_unfinished_copy(&self) -> FugaResult<Option<UnfinishedCopy>> {
+        let config = self.load_config()?;
+        Ok(config.data.unfinished_copy)
+    }
+
+    fn set_unfinished_copy(&self, copy: Option<&UnfinishedCopy>) -> FugaResult<()> {
+        let mut config = self.load_config()?;
+        config.data.unfinished_copy = copy.cloned();
+        self.store_config(&config)
+    }
+
+    fn get_mark_filters(&self) -> FugaResult<BTreeMap<String, PathFilters>> {
+        let config = self.load_config()?;
+        Ok(config.data.mark_filters)
+    }
+
+    fn set_mark_filters(&self, path: &str, filters: &PathFilters) -> FugaResult<()> {
+        let mut config = self.load_config()?;
+        if filters.is_empty() {
+            config.data.mark_filters.remove(path);
+        } else {
+            config
+                .data
+                .mark_filters
+                .insert(path.to_string(), filters.clone());
+        }
+        self.store_config(&config)
+    }
 }
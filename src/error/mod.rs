@@ -1,3 +1,3 @@
 pub mod types;
 
-pub use types::{FugaError, FugaResult};
+pub use types::{FugaError, FugaResult, CANCELLED_EXIT_CODE};
@@ -1,13 +1,46 @@
+/// Exit status after the user cancelled with Ctrl-C, following the 128 + SIGINT convention
+pub const CANCELLED_EXIT_CODE: i32 = 130;
+
 #[derive(Debug)]
 pub enum FugaError {
     ConfigError(confy::ConfyError),
     ConfigPathMissing,
     IoError(std::io::Error),
     FileNotFound(String),
-    PermissionError { path: String, message: String },
+    PermissionError {
+        path: String,
+        message: String,
+    },
     OperationFailed(String),
-    DuplicatePath { source: String, destination: String },
+    DuplicatePath {
+        source: String,
+        destination: String,
+    },
     FileSystemError(String),
+    DestinationExists(String),
+    PreflightFailed(Vec<FugaError>),
+    RollbackFailed {
+        cause: Box<FugaError>,
+        problems: Vec<FugaError>,
+    },
+    BatchIncomplete {
+        failed: usize,
+        total: usize,
+    },
+    CrossDevice {
+        source: String,
+        destination: String,
+    },
+    ChecksumMismatch {
+        destination: String,
+        files: Vec<String>,
+        /// What became of the copy that failed verification
+        outcome: String,
+    },
+    Cancelled {
+        /// Marked targets that were not completed
+        remaining: Vec<String>,
+    },
 }
 
 impl std::fmt::Display for FugaError {
@@ -29,6 +62,70 @@ impl std::fmt::Display for FugaError {
                 "Source and destination are the same: {source} -> {destination}"
             ),
             FugaError::FileSystemError(msg) => write!(f, "File system error: {msg}"),
+            FugaError::DestinationExists(path) => {
+                write!(f, "Destination already exists: {path}")
+            }
+            FugaError::PreflightFailed(problems) => {
+                write!(
+                    f,
+                    "Pre-flight check found {} problem(s); nothing was changed:",
+                    problems.len()
+                )?;
+                for problem in problems {
+                    write!(f, "\n  - {problem}")?;
+                }
+                Ok(())
+            }
+            FugaError::RollbackFailed { cause, problems } => {
+                write!(
+                    f,
+                    "{cause}\nRollback left {} step(s) in place:",
+                    problems.len()
+                )?;
+                for problem in problems {
+                    write!(f, "\n  - {problem}")?;
+                }
+                Ok(())
+            }
+            FugaError::BatchIncomplete { failed, total } => {
+                write!(f, "{failed} of {total} target(s) failed")
+            }
+            FugaError::CrossDevice {
+                source,
+                destination,
+            } => write!(
+                f,
+                "Cannot hard-link {source} to {destination}: they are on different filesystems"
+            ),
+            FugaError::ChecksumMismatch {
+                destination,
+                files,
+                outcome,
+            } => {
+                write!(
+                    f,
+                    "Verification of {destination} failed; {} file(s) differ from the source:",
+                    files.len()
+                )?;
+                for file in files {
+                    write!(f, "\n  - {file}")?;
+                }
+                write!(f, "\n{outcome}")
+            }
+            FugaError::Cancelled { remaining } if remaining.is_empty() => {
+                write!(f, "Cancelled")
+            }
+            FugaError::Cancelled { remaining } => {
+                write!(
+                    f,
+                    "Cancelled; {} target(s) did not complete:",
+                    remaining.len()
+                )?;
+                for target in remaining {
+                    write!(f, "\n  - {target}")?;
+                }
+                Ok(())
+            }
         }
     }
 }
@@ -70,6 +167,23 @@ impl FugaError {
         FugaError::FileNotFound(path.to_string())
     }
 
+    /// Whether the user cancelled, even if a rollback failed afterwards
+    pub fn is_cancelled(&self) -> bool {
+        match self {
+            FugaError::Cancelled { .. } => true,
+            FugaError::RollbackFailed { cause, .. } => cause.is_cancelled(),
+            _ => false,
+        }
+    }
+
+    /// Process exit status for this error
+    pub fn exit_code(&self) -> i32 {
+        match self.is_cancelled() {
+            true => CANCELLED_EXIT_CODE,
+            false => 1,
+        }
+    }
+
     /// Convert an IO error with path context
     pub fn from_io_error(err: std::io::Error, path: &str) -> Self {
         match err.kind() {
@@ -1,25 +1,388 @@
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
 use std::env;
 
 /// The application's name.
 pub const APP_NAME: &str = "fuga";
 
 /// The type of the target file or directory
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum TargetType {
     File,
     Dir,
+    /// A symbolic link itself, whatever it points to
+    Symlink,
+    /// A fifo, socket, or device
+    Other,
     None,
 }
 
+/// Kind of entry `mark --type` looks for, named like `find -type`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum EntryType {
+    /// Regular files
+    #[value(name = "f", alias = "file")]
+    File,
+    /// Directories
+    #[value(name = "d", alias = "dir")]
+    Dir,
+    /// Symbolic links, whatever they point to
+    #[value(name = "l", alias = "link")]
+    Symlink,
+}
+
+/// How `mark --list` prints the marks
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum ListFormat {
+    /// Type icons and stored filters
+    #[default]
+    Pretty,
+    /// Bare paths, one per line
+    Plain,
+}
+
 /// Consolidated file information to reduce system calls
+///
+/// `is_file` and `is_dir` describe what a symbolic link points to, so both
+/// are false for a dangling link, which still `exists`.
 #[derive(Debug, Clone)]
 pub struct FileInfo {
     pub exists: bool,
     pub is_file: bool,
     pub is_dir: bool,
+    pub is_symlink: bool,
+    /// Size in bytes of what the path points to
+    pub size: u64,
+    /// Modification time in seconds since the Unix epoch
+    pub modified: Option<u64>,
     pub name: Option<String>,
 }
 
+impl FileInfo {
+    pub fn target_type(&self) -> TargetType {
+        if !self.exists {
+            TargetType::None
+        } else if self.is_symlink {
+            TargetType::Symlink
+        } else if self.is_file {
+            TargetType::File
+        } else if self.is_dir {
+            TargetType::Dir
+        } else {
+            TargetType::Other
+        }
+    }
+}
+
+/// The file operation applied to the marked targets
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OperationKind {
+    Copy,
+    Move,
+    Link,
+}
+
+impl OperationKind {
+    /// Lowercase verb used in user-facing messages
+    pub fn verb(self) -> &'static str {
+        match self {
+            OperationKind::Copy => "copy",
+            OperationKind::Move => "move",
+            OperationKind::Link => "link",
+        }
+    }
+}
+
+/// How to handle a destination path that already exists
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum ConflictPolicy {
+    /// Replace the existing destination
+    #[default]
+    Overwrite,
+    /// Leave the existing destination untouched and skip the target
+    Skip,
+    /// Pick a free name such as `name (1).ext`
+    Rename,
+    /// Move the existing destination to `name~` or `name.~N~` first
+    Backup,
+    /// Abort with an error
+    Fail,
+    /// Prompt for one of the other policies
+    Ask,
+}
+
+/// When copies may share data blocks with their source
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum ReflinkMode {
+    /// Clone when the filesystem supports it, otherwise copy the data
+    #[default]
+    Auto,
+    /// Clone or fail
+    Always,
+    /// Always copy the data
+    Never,
+}
+
+/// Hash used by `--verify` to compare copied files with their source
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum ChecksumAlgorithm {
+    /// XXH3, a fast non-cryptographic hash
+    #[default]
+    Fast,
+    /// SHA-256
+    Sha256,
+}
+
+/// File metadata that `--preserve` can carry over to copies
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum PreserveAttr {
+    /// Permission bits
+    Mode,
+    /// Access and modification times
+    Timestamps,
+    /// Owning user and group
+    Ownership,
+    /// Extended attributes
+    Xattr,
+}
+
+/// Which metadata a copy keeps from its source
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PreserveOptions {
+    pub mode: bool,
+    pub timestamps: bool,
+    pub ownership: bool,
+    pub xattr: bool,
+}
+
+impl PreserveOptions {
+    /// Everything, as requested by `--archive`
+    pub fn all() -> Self {
+        Self {
+            mode: true,
+            timestamps: true,
+            ownership: true,
+            xattr: true,
+        }
+    }
+
+    pub fn from_attrs(attrs: &[PreserveAttr]) -> Self {
+        let mut options = Self::default();
+        for attr in attrs {
+            match attr {
+                PreserveAttr::Mode => options.mode = true,
+                PreserveAttr::Timestamps => options.timestamps = true,
+                PreserveAttr::Ownership => options.ownership = true,
+                PreserveAttr::Xattr => options.xattr = true,
+            }
+        }
+        options
+    }
+
+    pub fn any(&self) -> bool {
+        self.mode || self.timestamps || self.ownership || self.xattr
+    }
+}
+
+/// Which entries inside a directory target are copied, moved, or synced.
+///
+/// Patterns without a `/` match entry names at any depth; patterns with one
+/// match the path relative to the marked directory. Includes win over
+/// excludes and `.gitignore` rules.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PathFilters {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub include: Vec<String>,
+    /// Skip what git would ignore, and the `.git` directory itself
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub respect_gitignore: bool,
+}
+
+impl PathFilters {
+    pub fn is_empty(&self) -> bool {
+        self.exclude.is_empty() && self.include.is_empty() && !self.respect_gitignore
+    }
+
+    /// Combine these filters with the ones stored for a mark
+    pub fn merged(&self, other: &PathFilters) -> Self {
+        let mut merged = self.clone();
+        for pattern in &other.exclude {
+            if !merged.exclude.contains(pattern) {
+                merged.exclude.push(pattern.clone());
+            }
+        }
+        for pattern in &other.include {
+            if !merged.include.contains(pattern) {
+                merged.include.push(pattern.clone());
+            }
+        }
+        merged.respect_gitignore |= other.respect_gitignore;
+        merged
+    }
+}
+
+/// Options shared by copy, move, and link operations
+#[derive(Debug, Clone, Default)]
+pub struct TransferOptions {
+    pub on_conflict: ConflictPolicy,
+    /// Remove a partially written destination when the operation fails
+    pub cleanup_on_failure: bool,
+    /// Metadata to carry over when data is copied
+    pub preserve: PreserveOptions,
+    /// Create hard links instead of symbolic links
+    pub hard_link: bool,
+    /// Point symbolic links at their target with a path relative to the link
+    pub relative_link: bool,
+    /// Whether copied files may be copy-on-write clones of their source
+    pub reflink: ReflinkMode,
+    /// Number of files copied concurrently inside a directory
+    pub jobs: usize,
+    /// Compare every copied file with its source before the operation counts as done
+    pub verify: Option<ChecksumAlgorithm>,
+    /// Keep destination files an interrupted earlier copy already wrote
+    pub resume: bool,
+    /// Entries of directory targets to leave out
+    pub filters: PathFilters,
+    /// Copy what symbolic links point to instead of recreating the links
+    pub dereference: bool,
+}
+
+impl TransferOptions {
+    /// Copy of these options using a different conflict policy
+    pub fn with_conflict(&self, on_conflict: ConflictPolicy) -> Self {
+        let mut options = self.clone();
+        options.on_conflict = on_conflict;
+        options
+    }
+
+    /// Copy of these options that also applies the filters stored for a mark
+    pub fn with_mark_filters(&self, filters: Option<&PathFilters>) -> Self {
+        let mut options = self.clone();
+        if let Some(filters) = filters {
+            options.filters = options.filters.merged(filters);
+        }
+        options
+    }
+}
+
+/// Result of a single copy, move, or link operation
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransferOutcome {
+    /// The path that was finally written, which differs from the request when renamed
+    pub destination: String,
+    /// Where a pre-existing destination was moved before writing
+    pub backup: Option<String>,
+    /// Whether the target was left alone because of the conflict policy
+    pub skipped: bool,
+    /// Whether a move had to copy and delete, because it crossed filesystems, was filtered,
+    /// or followed links
+    pub cross_device: bool,
+    /// What the operation found at the destination
+    pub prior: PriorDestination,
+}
+
+/// What was at a destination before an operation wrote to it
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PriorDestination {
+    /// Nothing, or something that was moved to a backup first
+    #[default]
+    Vacant,
+    /// A directory the operation merged into, with the entries it created there
+    Merged(Vec<String>),
+    /// Entries the operation replaced in place, which cannot be restored
+    Overwritten,
+}
+
+impl PriorDestination {
+    pub fn is_vacant(&self) -> bool {
+        *self == PriorDestination::Vacant
+    }
+}
+
+/// Options for mirroring a target with `fuga sync`
+#[derive(Debug, Clone, Default)]
+pub struct SyncOptions {
+    /// Compare file contents with this hash instead of size and modification time
+    pub checksum: Option<ChecksumAlgorithm>,
+    /// Remove destination entries that do not exist in the source
+    pub delete: bool,
+    /// Report the changes without making them
+    pub dry_run: bool,
+    /// Whether copied files may be copy-on-write clones of their source
+    pub reflink: ReflinkMode,
+    /// Number of files copied concurrently
+    pub jobs: usize,
+    /// Entries of directory targets to leave out; excluded destination entries are never deleted
+    pub filters: PathFilters,
+    /// Copy what symbolic links point to instead of mirroring the links; dangling ones are always mirrored
+    pub dereference: bool,
+}
+
+impl SyncOptions {
+    /// Copy of these options that also applies the filters stored for a mark
+    pub fn with_mark_filters(&self, filters: Option<&PathFilters>) -> Self {
+        let mut options = self.clone();
+        if let Some(filters) = filters {
+            options.filters = options.filters.merged(filters);
+        }
+        options
+    }
+}
+
+/// How a destination entry differs from the source
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncChangeKind {
+    Added,
+    Updated,
+    Deleted,
+}
+
+/// One entry a sync wrote or removed, relative to the synced destination
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyncChange {
+    pub kind: SyncChangeKind,
+    pub path: String,
+}
+
+/// What a sync of one target changed
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SyncSummary {
+    pub changes: Vec<SyncChange>,
+    /// Files that already matched and were left alone
+    pub unchanged: usize,
+}
+
+impl SyncSummary {
+    pub fn count(&self, kind: SyncChangeKind) -> usize {
+        self.changes
+            .iter()
+            .filter(|change| change.kind == kind)
+            .count()
+    }
+}
+
+/// A completed operation on one target, with enough detail to reverse it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OperationStep {
+    pub kind: OperationKind,
+    /// Absolute path of the marked target
+    pub source: String,
+    /// Absolute path that was written
+    pub destination: String,
+    /// Absolute path the previous destination was moved to, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backup: Option<String>,
+    /// What was at the destination when nothing was backed up
+    #[serde(default, skip_serializing_if = "PriorDestination::is_vacant")]
+    pub prior: PriorDestination,
+}
+
 // Legacy functions removed - functionality migrated to service layer
 
 /// Get version of this tool.
@@ -7,7 +7,7 @@ mod traits;
 mod tui;
 mod ui;
 
-use clap::{Args, CommandFactory, Parser, Subcommand, ValueHint};
+use clap::{ArgGroup, Args, CommandFactory, Parser, Subcommand, ValueHint};
 use clap_complete::Shell;
 use once_cell::sync::Lazy;
 
@@ -15,13 +15,21 @@ use once_cell::sync::Lazy;
 use commands::{
     completion::CompletionCommand,
     copy::CopyCommand,
+    history::{parse_since, HistoryCommand},
     link::LinkCommand,
-    mark::{MarkAction, MarkCommand},
+    mark::{parse_size, ListStyle, MarkAction, MarkArgs, MarkCommand, MarkSearch, PathList},
     preset::{PresetAction, PresetCommand},
     r#move::MoveCommand,
-    Command as FugaCommand,
+    sync::{SyncArgs, SyncCommand},
+    undo::UndoCommand,
+    Command as FugaCommand, TransferArgs,
 };
 use config::FileConfigRepository;
+use fuga::{
+    ChecksumAlgorithm, ConflictPolicy, EntryType, ListFormat, PathFilters, PreserveAttr,
+    PreserveOptions, ReflinkMode,
+};
+use services::filter::parse_pattern;
 use services::{StandardFileSystemService, StandardPathService};
 use tui::dashboard::{run_dashboard, DashboardExit};
 use ui::TerminalUIService;
@@ -49,18 +57,117 @@ enum Commands {
         /// The name for the copied file or directory
         #[arg(value_hint = ValueHint::AnyPath, value_name = "DESTINATION")]
         destination: Option<String>,
+
+        /// Use copy-on-write clones where the filesystem supports them
+        #[arg(
+            long = "reflink",
+            value_enum,
+            value_name = "WHEN",
+            default_value_t = ReflinkMode::Auto,
+            num_args = 0..=1,
+            require_equals = true,
+            default_missing_value = "always"
+        )]
+        reflink: ReflinkMode,
+
+        /// Continue an interrupted copy, keeping destination files it already wrote
+        #[arg(long = "resume", conflicts_with = "on_conflict")]
+        resume: bool,
+
+        #[command(flatten)]
+        flags: TransferFlags,
+
+        #[command(flatten)]
+        data: CopyDataFlags,
     },
     /// Move the marked file or directory
     Move {
         /// The name for the moved file or directory
         #[arg(value_hint = ValueHint::AnyPath, value_name = "DESTINATION")]
         destination: Option<String>,
+
+        #[command(flatten)]
+        flags: TransferFlags,
+
+        #[command(flatten)]
+        data: CopyDataFlags,
     },
     /// Make a symbolic link to the marked file or directory
     Link {
         /// The name for the symbolic link
         #[arg(value_hint = ValueHint::AnyPath, value_name = "DESTINATION")]
         destination: Option<String>,
+
+        /// Create hard links; marked directories are recreated with every file inside hard-linked
+        #[arg(long = "hard")]
+        hard: bool,
+
+        /// Link with a path relative to the link's directory [default: from config]
+        #[arg(long = "relative", conflicts_with_all = ["hard", "absolute"])]
+        relative: bool,
+
+        /// Link with the absolute path even if relative links are configured
+        #[arg(long = "absolute", conflicts_with = "hard")]
+        absolute: bool,
+
+        #[command(flatten)]
+        flags: TransferFlags,
+    },
+    /// Mirror the marked files or directories, copying only what changed
+    Sync {
+        /// The name for the mirrored file or directory
+        #[arg(value_hint = ValueHint::AnyPath, value_name = "DESTINATION")]
+        destination: Option<String>,
+
+        /// Remove files in the destination that are not in the source
+        #[arg(long = "delete")]
+        delete: bool,
+
+        /// Compare file contents instead of size and modification time [default hash: fast]
+        #[arg(
+            long = "checksum",
+            value_enum,
+            value_name = "HASH",
+            num_args = 0..=1,
+            require_equals = true,
+            default_missing_value = "fast"
+        )]
+        checksum: Option<ChecksumAlgorithm>,
+
+        /// Show what would change without touching the destination
+        #[arg(long = "dry-run")]
+        dry_run: bool,
+
+        /// Copy up to N files at once; 0 uses one per CPU [default: from config, or 1]
+        #[arg(long = "jobs", short = 'j', value_name = "N")]
+        jobs: Option<usize>,
+
+        /// Copy what symbolic links point to [default]
+        #[arg(long = "dereference", short = 'L', conflicts_with = "no_dereference")]
+        dereference: bool,
+
+        /// Mirror symbolic links as links
+        #[arg(long = "no-dereference", short = 'P')]
+        no_dereference: bool,
+
+        #[command(flatten)]
+        filters: FilterFlags,
+    },
+    /// Undo the most recent copy, move, or link
+    Undo,
+    /// Show past copy, move, and link operations
+    History {
+        /// Show at most this many entries
+        #[arg(long = "limit", value_name = "N")]
+        limit: Option<usize>,
+
+        /// Only show entries since a date (YYYY-MM-DD[ HH:MM[:SS]], UTC) or an age such as 2h or 3d
+        #[arg(long = "since", value_name = "WHEN", value_parser = parse_since)]
+        since: Option<u64>,
+
+        /// Print the entries as JSON
+        #[arg(long = "json")]
+        json: bool,
     },
     /// Generate the completion script
     Completion {
@@ -78,22 +185,254 @@ enum Commands {
 }
 
 #[derive(Args, Debug, PartialEq)]
+#[command(group(ArgGroup::new("search").args(["glob", "recursive"]).multiple(true)))]
+#[command(group(ArgGroup::new("path_list").args(["stdin", "from_file"])))]
 struct Mark {
-    /// Paths you want to mark
-    #[arg(value_hint = ValueHint::AnyPath, value_name = "PATH", num_args = 0.., conflicts_with_all = ["list", "reset"])]
+    /// Paths you want to mark, or with --recursive the directories to search
+    #[arg(value_hint = ValueHint::AnyPath, value_name = "PATH", num_args = 0.., conflicts_with_all = ["list", "reset", "remove", "toggle"])]
     paths: Vec<String>,
 
     /// Add the provided paths to the existing mark list
-    #[arg(long = "add", conflicts_with_all = ["list", "reset"])]
+    #[arg(long = "add", conflicts_with_all = ["list", "reset", "remove", "toggle"])]
     add: bool,
 
+    /// Unmark paths, positions in --list (from 1), or globs matched against the marked paths
+    #[arg(
+        long = "remove",
+        value_name = "PATH|INDEX|GLOB",
+        num_args = 1..,
+        conflicts_with_all = ["list", "reset", "toggle", "note", "exclude", "include", "respect_gitignore"]
+    )]
+    remove: Vec<String>,
+
+    /// Unmark the paths that are marked and mark the others
+    #[arg(
+        long = "toggle",
+        value_hint = ValueHint::AnyPath,
+        value_name = "PATH",
+        num_args = 1..,
+        conflicts_with_all = ["list", "reset"]
+    )]
+    toggle: Vec<String>,
+
+    /// Mark the paths matching a glob such as '**/*.log'; quote it so the shell leaves it alone
+    #[arg(
+        long = "glob",
+        value_name = "PATTERN",
+        value_parser = parse_pattern,
+        num_args = 1..,
+        conflicts_with_all = ["list", "reset", "remove", "toggle"]
+    )]
+    glob: Vec<String>,
+
+    /// Mark the entries below the given directories (default: the current one) instead of the directories
+    #[arg(long = "recursive", short = 'r', conflicts_with_all = ["list", "reset", "remove", "toggle"])]
+    recursive: bool,
+
+    /// Only mark entries whose name matches a glob, like `find -name`
+    #[arg(long = "name", value_name = "PATTERN", value_parser = parse_pattern, requires = "search")]
+    name: Vec<String>,
+
+    /// Only mark files (f), directories (d), or symbolic links (l)
+    #[arg(long = "type", value_enum, value_name = "TYPE", requires = "search")]
+    entry_type: Option<EntryType>,
+
+    /// Only mark entries modified since a date (YYYY-MM-DD[ HH:MM[:SS]], UTC) or within an age like 2h, 3d
+    #[arg(long = "newer", value_name = "WHEN", value_parser = parse_since, requires = "search")]
+    newer: Option<u64>,
+
+    /// Only mark entries larger than a size like 512, 10K, or 1.5M
+    #[arg(long = "larger", value_name = "SIZE", value_parser = parse_size, requires = "search")]
+    larger: Option<u64>,
+
+    /// Also mark the paths read from stdin, one per line
+    #[arg(long = "stdin", conflicts_with_all = ["list", "reset", "remove", "toggle"])]
+    stdin: bool,
+
+    /// Also mark the paths listed in a file, one per line
+    #[arg(
+        long = "from-file",
+        value_hint = ValueHint::FilePath,
+        value_name = "FILE",
+        conflicts_with_all = ["list", "reset", "remove", "toggle"]
+    )]
+    from_file: Option<String>,
+
+    /// Read paths separated by NUL bytes, as written by `find -print0`
+    #[arg(long = "null", short = '0', requires = "path_list")]
+    null: bool,
+
     /// List the marked targets
-    #[arg(long = "list", conflicts_with = "reset")]
+    #[arg(long = "list", conflicts_with_all = ["reset", "exclude", "include", "respect_gitignore"])]
     list: bool,
 
+    /// Show the type, size, and modification time recorded when each target was marked
+    #[arg(long = "long", short = 'l', requires = "list", conflicts_with_all = ["format", "print0"])]
+    long: bool,
+
+    /// Print the list with icons (pretty) or as bare paths, one per line (plain)
+    #[arg(long = "format", value_enum, value_name = "FORMAT", requires = "list")]
+    format: Option<ListFormat>,
+
+    /// Print the list as bare paths each ending with a NUL byte, for `xargs -0`
+    #[arg(long = "print0", requires = "list")]
+    print0: bool,
+
+    /// Report marks whose path was deleted, moved, or changed type since it was marked
+    #[arg(
+        long = "check",
+        conflicts_with_all = ["paths", "add", "list", "reset", "remove", "toggle", "prune", "search", "path_list", "note", "exclude", "include", "respect_gitignore"]
+    )]
+    check: bool,
+
+    /// Unmark the targets --check reports
+    #[arg(
+        long = "prune",
+        conflicts_with_all = ["paths", "add", "list", "reset", "remove", "toggle", "search", "path_list", "note", "exclude", "include", "respect_gitignore"]
+    )]
+    prune: bool,
+
     /// Reset the mark list
-    #[arg(long = "reset", conflicts_with = "list")]
+    #[arg(long = "reset", conflicts_with_all = ["list", "exclude", "include", "respect_gitignore", "note"])]
     reset: bool,
+
+    /// Note to keep with the marked paths, shown by --list --long
+    #[arg(long = "note", value_name = "TEXT", conflicts_with = "list")]
+    note: Option<String>,
+
+    /// Filters remembered for the marked directories
+    #[command(flatten)]
+    filters: FilterFlags,
+}
+
+/// Flags that leave entries of marked directories out
+#[derive(Args, Debug, PartialEq)]
+struct FilterFlags {
+    /// Skip entries matching a glob; a pattern with a `/` matches the path inside the marked directory
+    #[arg(long = "exclude", value_name = "GLOB", value_parser = parse_pattern)]
+    exclude: Vec<String>,
+
+    /// Keep entries matching a glob even if an exclude or .gitignore rule matches them
+    #[arg(long = "include", value_name = "GLOB", value_parser = parse_pattern)]
+    include: Vec<String>,
+
+    /// Skip what git would ignore, and the .git directory
+    #[arg(long = "respect-gitignore")]
+    respect_gitignore: bool,
+}
+
+impl FilterFlags {
+    fn into_filters(self) -> PathFilters {
+        PathFilters {
+            exclude: self.exclude,
+            include: self.include,
+            respect_gitignore: self.respect_gitignore,
+        }
+    }
+}
+
+#[derive(Args, Debug, PartialEq)]
+struct TransferFlags {
+    /// How to handle destinations that already exist [default: from config]
+    #[arg(long = "on-conflict", value_enum, value_name = "POLICY")]
+    on_conflict: Option<ConflictPolicy>,
+
+    /// Show what would happen to each marked target without changing anything
+    #[arg(long = "dry-run")]
+    dry_run: bool,
+
+    /// Undo already completed targets if a later one fails
+    #[arg(long = "atomic", conflicts_with = "dry_run")]
+    atomic: bool,
+
+    /// Attempt every target even if some fail, then print a summary
+    #[arg(long = "keep-going", conflicts_with = "atomic")]
+    keep_going: bool,
+}
+
+impl TransferFlags {
+    fn into_args(self) -> TransferArgs {
+        TransferArgs {
+            on_conflict: self.on_conflict,
+            dry_run: self.dry_run,
+            atomic: self.atomic,
+            keep_going: self.keep_going,
+            preserve: PreserveOptions::default(),
+            hard_link: false,
+            relative_link: None,
+            reflink: ReflinkMode::Auto,
+            jobs: None,
+            verify: None,
+            resume: false,
+            filters: PathFilters::default(),
+            dereference: None,
+        }
+    }
+}
+
+/// Flags for the commands that may write copies of file data
+#[derive(Args, Debug, PartialEq)]
+struct CopyDataFlags {
+    /// Metadata to keep on the copied data (comma separated)
+    #[arg(
+        long = "preserve",
+        value_enum,
+        value_name = "ATTRS",
+        value_delimiter = ','
+    )]
+    preserve: Vec<PreserveAttr>,
+
+    /// Preserve mode, timestamps, ownership, and extended attributes
+    #[arg(long = "archive", short = 'a', conflicts_with = "preserve")]
+    archive: bool,
+
+    /// Copy up to N files of a directory at once; 0 uses one per CPU [default: from config, or 1]
+    #[arg(long = "jobs", short = 'j', value_name = "N")]
+    jobs: Option<usize>,
+
+    /// Checksum every copied file against its source [default hash: fast]
+    #[arg(
+        long = "verify",
+        value_enum,
+        value_name = "HASH",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "fast"
+    )]
+    verify: Option<ChecksumAlgorithm>,
+
+    /// Copy what symbolic links point to [default for copy]
+    #[arg(long = "dereference", short = 'L', conflicts_with = "no_dereference")]
+    dereference: bool,
+
+    /// Recreate symbolic links as links [default for move]
+    #[arg(long = "no-dereference", short = 'P')]
+    no_dereference: bool,
+
+    #[command(flatten)]
+    filters: FilterFlags,
+}
+
+impl CopyDataFlags {
+    fn apply(self, args: TransferArgs) -> TransferArgs {
+        let preserve = if self.archive {
+            PreserveOptions::all()
+        } else {
+            PreserveOptions::from_attrs(&self.preserve)
+        };
+        TransferArgs {
+            preserve,
+            jobs: self.jobs,
+            verify: self.verify,
+            filters: self.filters.into_filters(),
+            dereference: match (self.dereference, self.no_dereference) {
+                (true, _) => Some(true),
+                (_, true) => Some(false),
+                _ => None,
+            },
+            ..args
+        }
+    }
 }
 
 #[derive(Subcommand, Debug, PartialEq)]
@@ -156,21 +495,49 @@ fn main() {
 
     let result = match opt.command {
         Some(Commands::Mark(mark)) => {
+            let search = MarkSearch {
+                globs: mark.glob,
+                recursive: mark.recursive,
+                names: mark.name,
+                entry_type: mark.entry_type,
+                newer: mark.newer,
+                larger: mark.larger,
+            };
+            let path_list = (mark.stdin || mark.from_file.is_some()).then_some(PathList {
+                file: mark.from_file,
+                nul: mark.null,
+            });
             let action = if mark.list {
-                MarkAction::List
+                MarkAction::List(if mark.print0 {
+                    ListStyle::Bare { terminator: '\0' }
+                } else if mark.format == Some(ListFormat::Plain) {
+                    ListStyle::Bare { terminator: '\n' }
+                } else if mark.long {
+                    ListStyle::Long
+                } else {
+                    ListStyle::Short
+                })
             } else if mark.reset {
                 MarkAction::Reset
+            } else if mark.check {
+                MarkAction::Check
+            } else if mark.prune {
+                MarkAction::Prune
+            } else if !mark.remove.is_empty() {
+                MarkAction::Remove(mark.remove)
+            } else if !mark.toggle.is_empty() {
+                MarkAction::Toggle(mark.toggle)
             } else if mark.add {
-                if mark.paths.is_empty() {
+                if mark.paths.is_empty() && !search.is_active() && path_list.is_none() {
                     eprintln!("❌ : --add requires at least one path to mark");
                     std::process::exit(1);
                 }
                 MarkAction::Add(mark.paths)
-            } else if !mark.paths.is_empty() {
+            } else if !mark.paths.is_empty() || search.is_active() || path_list.is_some() {
                 MarkAction::Set(mark.paths)
             } else {
                 eprintln!(
-                    "❌ : Provide at least one path, --add with paths, or use --list/--reset"
+                    "❌ : Provide at least one path, --add with paths, or use --list/--reset/--remove/--toggle/--check/--prune"
                 );
                 std::process::exit(1);
             };
@@ -180,43 +547,126 @@ fn main() {
                 &services.fs_service,
                 &services.ui_service,
                 action,
+                MarkArgs {
+                    filters: mark.filters.into_filters(),
+                    note: mark.note,
+                    search,
+                    path_list,
+                },
             );
 
             execute_command(command)
         }
-        Some(Commands::Copy { destination }) => {
+        Some(Commands::Copy {
+            destination,
+            reflink,
+            resume,
+            flags,
+            data,
+        }) => {
             let command = CopyCommand::new(
                 &services.config_repo,
                 &services.fs_service,
                 &services.ui_service,
                 &services.path_service,
                 destination,
+                data.apply(TransferArgs {
+                    reflink,
+                    resume,
+                    ..flags.into_args()
+                }),
             );
 
             execute_command(command)
         }
-        Some(Commands::Move { destination }) => {
+        Some(Commands::Move {
+            destination,
+            flags,
+            data,
+        }) => {
             let command = MoveCommand::new(
                 &services.config_repo,
                 &services.fs_service,
                 &services.ui_service,
                 &services.path_service,
                 destination,
+                data.apply(flags.into_args()),
             );
 
             execute_command(command)
         }
-        Some(Commands::Link { destination }) => {
+        Some(Commands::Link {
+            destination,
+            hard,
+            relative,
+            absolute,
+            flags,
+        }) => {
             let command = LinkCommand::new(
                 &services.config_repo,
                 &services.fs_service,
                 &services.ui_service,
                 &services.path_service,
                 destination,
+                TransferArgs {
+                    hard_link: hard,
+                    relative_link: match (relative, absolute) {
+                        (true, _) => Some(true),
+                        (_, true) => Some(false),
+                        _ => None,
+                    },
+                    ..flags.into_args()
+                },
             );
 
             execute_command(command)
         }
+        Some(Commands::Sync {
+            destination,
+            delete,
+            checksum,
+            dry_run,
+            jobs,
+            dereference,
+            no_dereference,
+            filters,
+        }) => {
+            let command = SyncCommand::new(
+                &services.config_repo,
+                &services.fs_service,
+                &services.ui_service,
+                &services.path_service,
+                destination,
+                SyncArgs {
+                    checksum,
+                    delete,
+                    dry_run,
+                    jobs,
+                    // `-L` only restates the default
+                    dereference: dereference || !no_dereference,
+                    filters: filters.into_filters(),
+                },
+            );
+            execute_command(command)
+        }
+        Some(Commands::Undo) => {
+            let command = UndoCommand::new(
+                &services.config_repo,
+                &services.fs_service,
+                &services.ui_service,
+            );
+            execute_command(command)
+        }
+        Some(Commands::History { limit, since, json }) => {
+            let command = HistoryCommand::new(
+                &services.config_repo,
+                &services.ui_service,
+                limit,
+                since,
+                json,
+            );
+            execute_command(command)
+        }
         Some(Commands::Completion { shell }) => {
             let cmd = Opt::command();
             let command = CompletionCommand::new(shell, cmd);
@@ -254,6 +704,7 @@ fn main() {
                     &services.ui_service,
                     &services.path_service,
                     destination,
+                    TransferArgs::default(),
                 );
                 execute_command(command)
             }
@@ -265,6 +716,7 @@ fn main() {
                     &services.ui_service,
                     &services.path_service,
                     destination,
+                    TransferArgs::default(),
                 );
                 execute_command(command)
             }
@@ -276,6 +728,7 @@ fn main() {
                     &services.ui_service,
                     &services.path_service,
                     destination,
+                    TransferArgs::default(),
                 );
                 execute_command(command)
             }
@@ -286,6 +739,6 @@ fn main() {
     // Handle any errors that occurred during command execution
     if let Err(e) = result {
         eprintln!("❌ : {e}");
-        std::process::exit(1);
+        std::process::exit(e.exit_code());
     }
 }
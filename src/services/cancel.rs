@@ -0,0 +1,42 @@
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Once;
+
+static CANCELLED: AtomicBool = AtomicBool::new(false);
+static WATCH: Once = Once::new();
+
+/// Turn Ctrl-C into a cancellation request instead of killing the process.
+///
+/// Running copies notice the request between files and chunks and stop
+/// cleanly. A second Ctrl-C exits at once for when that takes too long.
+pub fn watch() {
+    WATCH.call_once(|| {
+        // Without a handler Ctrl-C simply keeps its default behaviour
+        let _ = ctrlc::set_handler(|| {
+            if CANCELLED.swap(true, Ordering::SeqCst) {
+                std::process::exit(crate::error::CANCELLED_EXIT_CODE);
+            }
+            eprintln!("\nCancelling after the current chunk; press Ctrl-C again to stop at once.");
+        });
+    });
+}
+
+pub fn is_cancelled() -> bool {
+    CANCELLED.load(Ordering::SeqCst)
+}
+
+/// Fail with a cancellation error once Ctrl-C was pressed
+pub fn check() -> io::Result<()> {
+    match is_cancelled() {
+        true => Err(io::Error::new(io::ErrorKind::Interrupted, "cancelled")),
+        false => Ok(()),
+    }
+}
+
+/// Whether an IO error came from [`check`].
+///
+/// Interrupted system calls are retried where they happen, so this kind only
+/// reaches callers for cancellations.
+pub fn is_cancellation(err: &io::Error) -> bool {
+    err.kind() == io::ErrorKind::Interrupted
+}
@@ -0,0 +1,128 @@
+use crate::fuga::ChecksumAlgorithm;
+use crate::services::copy_engine::{with_path, FileJob};
+use sha2::{Digest, Sha256};
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use xxhash_rust::xxh3::Xxh3;
+
+/// Size of each read while hashing
+const BUFFER_SIZE: usize = 1024 * 1024;
+
+/// Hash every source and destination of `files` and return the destinations that differ.
+pub fn verify_files(files: &[FileJob], algorithm: ChecksumAlgorithm) -> io::Result<Vec<PathBuf>> {
+    let mut mismatches = Vec::new();
+    for job in files {
+        let expected = file_digest(&job.src, algorithm, false)?;
+        let actual = file_digest(&job.dst, algorithm, true)?;
+        if expected != actual {
+            mismatches.push(job.dst.clone());
+        }
+    }
+    Ok(mismatches)
+}
+
+/// Hash a file's contents.
+///
+/// With `from_device`, the file is flushed and dropped from the page cache
+/// first where the platform allows it, so the digest reflects what actually
+/// reached the disk rather than what is still held in memory.
+pub fn file_digest(
+    path: &Path,
+    algorithm: ChecksumAlgorithm,
+    from_device: bool,
+) -> io::Result<Vec<u8>> {
+    let mut file = File::open(path).map_err(|e| with_path(e, path))?;
+    if from_device {
+        drop_cached_pages(&file).map_err(|e| with_path(e, path))?;
+    }
+
+    let mut buffer = vec![0; BUFFER_SIZE];
+    let mut hasher = Hasher::new(algorithm);
+    loop {
+        let read = match file.read(&mut buffer) {
+            Ok(0) => return Ok(hasher.finish()),
+            Ok(read) => read,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(with_path(e, path)),
+        };
+        hasher.update(&buffer[..read]);
+    }
+}
+
+enum Hasher {
+    Fast(Box<Xxh3>),
+    Sha256(Sha256),
+}
+
+impl Hasher {
+    fn new(algorithm: ChecksumAlgorithm) -> Self {
+        match algorithm {
+            ChecksumAlgorithm::Fast => Self::Fast(Box::default()),
+            ChecksumAlgorithm::Sha256 => Self::Sha256(Sha256::new()),
+        }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        match self {
+            Self::Fast(hasher) => hasher.update(data),
+            Self::Sha256(hasher) => hasher.update(data),
+        }
+    }
+
+    fn finish(self) -> Vec<u8> {
+        match self {
+            Self::Fast(hasher) => hasher.digest128().to_be_bytes().to_vec(),
+            Self::Sha256(hasher) => hasher.finalize().to_vec(),
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn drop_cached_pages(file: &File) -> io::Result<()> {
+    use std::os::unix::io::AsRawFd;
+
+    // Only clean pages can be evicted, so write back anything still pending first
+    file.sync_all()?;
+    // SAFETY: the descriptor is open for the duration of the call
+    let result = unsafe { libc::posix_fadvise(file.as_raw_fd(), 0, 0, libc::POSIX_FADV_DONTNEED) };
+    if result == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::from_raw_os_error(result))
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn drop_cached_pages(file: &File) -> io::Result<()> {
+    file.sync_all()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn verify_reports_only_the_files_that_differ() {
+        let dir = tempfile::tempdir().unwrap();
+        let files: Vec<FileJob> = (0..3)
+            .map(|index| {
+                let src = dir.path().join(format!("src-{index}"));
+                let dst = dir.path().join(format!("dst-{index}"));
+                fs::write(&src, vec![b'x'; 4096]).unwrap();
+                fs::write(&dst, vec![b'x'; 4096]).unwrap();
+                FileJob { src, dst }
+            })
+            .collect();
+        // Same length, one flipped byte
+        let mut corrupted = vec![b'x'; 4096];
+        corrupted[2048] = b'y';
+        fs::write(&files[1].dst, corrupted).unwrap();
+
+        for algorithm in [ChecksumAlgorithm::Fast, ChecksumAlgorithm::Sha256] {
+            let mismatches = verify_files(&files, algorithm).unwrap();
+            assert_eq!(mismatches, vec![files[1].dst.clone()]);
+        }
+    }
+}
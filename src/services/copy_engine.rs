@@ -0,0 +1,624 @@
+use crate::fuga::{PathFilters, PriorDestination, ReflinkMode};
+use crate::services::cancel;
+use crate::services::filter::{Scope, TreeFilter};
+use filetime::FileTime;
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+
+/// Size of each `copy_file_range` request and of the buffered fallback
+const CHUNK_SIZE: usize = 8 * 1024 * 1024;
+
+/// Progress notifications from a running copy
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CopyEvent {
+    /// This many more bytes were written
+    Bytes(u64),
+    /// Another file was completed
+    FileDone,
+}
+
+/// A single regular file to copy as part of a tree
+#[derive(Debug, Clone)]
+pub struct FileJob {
+    pub src: PathBuf,
+    pub dst: PathBuf,
+}
+
+/// A symbolic link to recreate as part of a tree
+#[derive(Debug, Clone)]
+pub struct LinkJob {
+    pub src: PathBuf,
+    /// What the link points to, exactly as stored in it
+    pub target: PathBuf,
+    pub dst: PathBuf,
+}
+
+/// Everything a copy has to create, gathered before any data is written
+#[derive(Debug, Default)]
+pub struct CopyPlan {
+    /// Destination directories, parents before children
+    pub dirs: Vec<PathBuf>,
+    /// Source directories, in the same order as `dirs`
+    pub src_dirs: Vec<PathBuf>,
+    pub files: Vec<FileJob>,
+    pub links: Vec<LinkJob>,
+    pub total_bytes: u64,
+}
+
+impl CopyPlan {
+    /// Walk `src` and map every entry that `filters` lets through to its place under `dst`.
+    ///
+    /// With `dereference`, symbolic links are followed so the copy contains
+    /// what they point to; otherwise they are recreated as links. Fifos,
+    /// sockets, and devices inside directories have no data and are left out.
+    pub fn build(
+        src: &Path,
+        dst: &Path,
+        filters: &PathFilters,
+        dereference: bool,
+    ) -> io::Result<Self> {
+        let filter = TreeFilter::new(filters)?;
+        let mut plan = Self::default();
+        plan.add(src, dst, &filter.scope(src), dereference)?;
+        Ok(plan)
+    }
+
+    /// Add `src`, using `scope` for its entries when it is a directory
+    fn add(&mut self, src: &Path, dst: &Path, scope: &Scope, dereference: bool) -> io::Result<()> {
+        let info = match dereference {
+            true => fs::metadata(src),
+            false => fs::symlink_metadata(src),
+        }
+        .map_err(|e| with_path(e, src))?;
+        if info.file_type().is_symlink() {
+            self.links.push(LinkJob {
+                src: src.to_path_buf(),
+                target: fs::read_link(src).map_err(|e| with_path(e, src))?,
+                dst: dst.to_path_buf(),
+            });
+            return Ok(());
+        }
+        if info.is_file() {
+            self.total_bytes += info.len();
+            self.files.push(FileJob {
+                src: src.to_path_buf(),
+                dst: dst.to_path_buf(),
+            });
+            return Ok(());
+        }
+        if !info.is_dir() {
+            return Ok(());
+        }
+
+        self.dirs.push(dst.to_path_buf());
+        self.src_dirs.push(src.to_path_buf());
+        let mut entries = fs::read_dir(src)
+            .map_err(|e| with_path(e, src))?
+            .collect::<io::Result<Vec<_>>>()
+            .map_err(|e| with_path(e, src))?;
+        // A stable order keeps progress output and error reporting reproducible
+        entries.sort_by_key(|entry| entry.file_name());
+        for entry in entries {
+            let path = entry.path();
+            let is_dir = match dereference {
+                true => fs::metadata(&path).is_ok_and(|info| info.is_dir()),
+                false => entry.file_type().is_ok_and(|kind| kind.is_dir()),
+            };
+            if !scope.allows(&path, is_dir) {
+                continue;
+            }
+            let child = is_dir.then(|| scope.child(&path));
+            self.add(
+                &path,
+                &dst.join(entry.file_name()),
+                child.as_ref().unwrap_or(scope),
+                dereference,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// What is already in place where the plan writes to `root`; call it before creating anything.
+    ///
+    /// Only the topmost created entries are listed for a merge, since removing
+    /// a created directory removes what was created inside it.
+    pub fn prior_destination(&self, root: &Path) -> PriorDestination {
+        let occupied = |path: &Path| fs::symlink_metadata(path).is_ok();
+        if !occupied(root) {
+            return PriorDestination::Vacant;
+        }
+        let written = self.files.iter().map(|job| &job.dst);
+        let written: Vec<&PathBuf> = written
+            .chain(self.links.iter().map(|link| &link.dst))
+            .collect();
+        if written.iter().any(|path| occupied(path)) {
+            return PriorDestination::Overwritten;
+        }
+
+        let mut created_dirs = HashSet::new();
+        let mut created = Vec::new();
+        for dir in self.dirs.iter().filter(|dir| !occupied(dir)) {
+            if !dir
+                .parent()
+                .is_some_and(|parent| created_dirs.contains(parent))
+            {
+                created.push(dir.display().to_string());
+            }
+            created_dirs.insert(dir.as_path());
+        }
+        for path in written {
+            if !path
+                .parent()
+                .is_some_and(|parent| created_dirs.contains(parent))
+            {
+                created.push(path.display().to_string());
+            }
+        }
+        PriorDestination::Merged(created)
+    }
+
+    /// Create every destination directory; existing ones are reused.
+    pub fn create_dirs(&self) -> io::Result<()> {
+        for dir in &self.dirs {
+            fs::create_dir_all(dir).map_err(|e| with_path(e, dir))?;
+        }
+        Ok(())
+    }
+
+    /// Recreate every symbolic link, replacing files or links already in its place
+    pub fn create_links(&self) -> io::Result<()> {
+        create_links(&self.links)
+    }
+}
+
+/// Recreate symbolic links, replacing files or links already in their place
+pub fn create_links(links: &[LinkJob]) -> io::Result<()> {
+    for link in links {
+        if fs::symlink_metadata(&link.dst).is_ok_and(|info| !info.is_dir()) {
+            fs::remove_file(&link.dst).map_err(|e| with_path(e, &link.dst))?;
+        }
+        make_symlink(link).map_err(|e| with_path(e, &link.dst))?;
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn make_symlink(link: &LinkJob) -> io::Result<()> {
+    std::os::unix::fs::symlink(&link.target, &link.dst)
+}
+
+#[cfg(windows)]
+fn make_symlink(link: &LinkJob) -> io::Result<()> {
+    // Windows needs to know whether the link points to a directory
+    match fs::metadata(&link.src).is_ok_and(|info| info.is_dir()) {
+        true => std::os::windows::fs::symlink_dir(&link.target, &link.dst),
+        false => std::os::windows::fs::symlink_file(&link.target, &link.dst),
+    }
+}
+
+/// How each file of a copy is written
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CopySettings {
+    pub reflink: ReflinkMode,
+    /// Keep what an interrupted earlier copy already wrote, see [`resume_offset`]
+    pub resume: bool,
+}
+
+/// Copy every file of a plan, `jobs` at a time, reporting to `progress`.
+///
+/// `progress` is only ever called on the calling thread. When several files
+/// fail, the error of the earliest one in plan order is returned, so the
+/// report does not depend on thread scheduling. A cancellation stops the copy
+/// before the next file is started.
+pub fn copy_files(
+    files: &[FileJob],
+    settings: CopySettings,
+    jobs: usize,
+    progress: &mut dyn FnMut(CopyEvent),
+) -> io::Result<()> {
+    if jobs <= 1 || files.len() <= 1 {
+        for job in files {
+            cancel::check()?;
+            copy_file(&job.src, &job.dst, settings, &mut |bytes| {
+                progress(CopyEvent::Bytes(bytes))
+            })?;
+            progress(CopyEvent::FileDone);
+        }
+        return Ok(());
+    }
+
+    let next = AtomicUsize::new(0);
+    let failed = AtomicBool::new(false);
+    let (sender, receiver) = mpsc::channel::<CopyEvent>();
+    let mut errors = thread::scope(|scope| {
+        let workers: Vec<_> = (0..jobs.min(files.len()))
+            .map(|_| {
+                let sender = sender.clone();
+                let (next, failed) = (&next, &failed);
+                scope.spawn(move || {
+                    let mut errors = Vec::new();
+                    let mut report = |bytes| {
+                        let _ = sender.send(CopyEvent::Bytes(bytes));
+                    };
+                    // Jobs are claimed in order, so every earlier job is attempted as well
+                    while !failed.load(Ordering::Relaxed) {
+                        let index = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(job) = files.get(index) else {
+                            break;
+                        };
+                        let copied = cancel::check()
+                            .and_then(|()| copy_file(&job.src, &job.dst, settings, &mut report));
+                        match copied {
+                            Ok(()) => {
+                                let _ = sender.send(CopyEvent::FileDone);
+                            }
+                            Err(err) => {
+                                failed.store(true, Ordering::Relaxed);
+                                errors.push((index, err));
+                            }
+                        }
+                    }
+                    errors
+                })
+            })
+            .collect();
+
+        drop(sender);
+        for event in receiver {
+            progress(event);
+        }
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().expect("copy worker panicked"))
+            .collect::<Vec<_>>()
+    });
+
+    errors.sort_by_key(|(index, _)| *index);
+    match errors.into_iter().next() {
+        Some((_, err)) => Err(err),
+        None => Ok(()),
+    }
+}
+
+/// Copy one regular file, reporting each chunk of written bytes to `progress`.
+///
+/// Tries a copy-on-write clone first when `reflink` allows it, then
+/// `copy_file_range`, then a plain buffered copy. The destination gets the
+/// source's permission bits, like `std::fs::copy`, and once it is complete its
+/// modification time, which is how a resumed copy recognizes finished files.
+/// When resuming, a complete destination is left alone and a partial one is
+/// continued where it stopped.
+///
+/// A cancellation stops the copy between chunks and removes the partial file.
+pub fn copy_file(
+    src: &Path,
+    dst: &Path,
+    settings: CopySettings,
+    progress: &mut dyn FnMut(u64),
+) -> io::Result<()> {
+    let result = copy_file_data(src, dst, settings, progress);
+    if matches!(&result, Err(e) if cancel::is_cancellation(e)) {
+        let _ = fs::remove_file(dst);
+    }
+    result
+}
+
+fn copy_file_data(
+    src: &Path,
+    dst: &Path,
+    settings: CopySettings,
+    progress: &mut dyn FnMut(u64),
+) -> io::Result<()> {
+    let mut reader = File::open(src).map_err(|e| with_path(e, src))?;
+    let info = reader.metadata().map_err(|e| with_path(e, src))?;
+
+    let offset = match settings.resume {
+        true => resume_offset(src, &info, dst).map_err(|e| with_path(e, dst))?,
+        false => None,
+    };
+    let mut writer = match offset {
+        Some(offset) if offset == info.len() => {
+            progress(offset);
+            return Ok(());
+        }
+        Some(offset) => {
+            let mut writer = fs::OpenOptions::new()
+                .write(true)
+                .open(dst)
+                .map_err(|e| with_path(e, dst))?;
+            writer
+                .seek(SeekFrom::Start(offset))
+                .and_then(|_| reader.seek(SeekFrom::Start(offset)))
+                .map_err(|e| with_path(e, dst))?;
+            progress(offset);
+            writer
+        }
+        None => File::create(dst).map_err(|e| with_path(e, dst))?,
+    };
+
+    let reflink = match offset {
+        // Only a whole file can be cloned
+        Some(_) => ReflinkMode::Never,
+        None => settings.reflink,
+    };
+    let cloned = match reflink {
+        ReflinkMode::Never => false,
+        ReflinkMode::Auto => try_reflink(&reader, &writer).is_ok(),
+        ReflinkMode::Always => {
+            try_reflink(&reader, &writer).map_err(|e| {
+                io::Error::new(
+                    e.kind(),
+                    format!("{}: reflink not possible: {e}", dst.display()),
+                )
+            })?;
+            true
+        }
+    };
+
+    if cloned {
+        progress(info.len());
+    } else {
+        let copied = copy_range(&reader, &writer, progress).map_err(|e| with_path(e, dst))?;
+        if !copied {
+            copy_buffered(&mut reader, &mut writer, progress).map_err(|e| with_path(e, dst))?;
+        }
+    }
+
+    writer
+        .set_permissions(info.permissions())
+        .map_err(|e| with_path(e, dst))
+}
+
+/// How many bytes of an earlier, interrupted copy at `dst` can be kept.
+///
+/// Only a destination written after the source last changed is trusted, and only
+/// when its last bytes match the source at the same position: at the source's
+/// size it counts as complete, shorter it is continued. Anything else is copied
+/// again from scratch.
+pub fn resume_offset(src: &Path, src_info: &fs::Metadata, dst: &Path) -> io::Result<Option<u64>> {
+    let dst_info = match fs::metadata(dst) {
+        Ok(info) => info,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    if !dst_info.is_file() {
+        return Ok(None);
+    }
+
+    let src_modified = FileTime::from_last_modification_time(src_info);
+    let dst_modified = FileTime::from_last_modification_time(&dst_info);
+    let kept = dst_info.len() <= src_info.len()
+        && dst_modified >= src_modified
+        && ends_like_source(src, dst, dst_info.len())?;
+    Ok(kept.then_some(dst_info.len()))
+}
+
+/// Bytes compared at the end of a partial file before it is continued
+const RESUME_CHECK_BYTES: u64 = 64 * 1024;
+
+/// Whether the last bytes before `len` are the same in both files
+fn ends_like_source(src: &Path, dst: &Path, len: u64) -> io::Result<bool> {
+    let count = len.min(RESUME_CHECK_BYTES);
+    let tail = |path: &Path| -> io::Result<Vec<u8>> {
+        let mut file = File::open(path)?;
+        file.seek(SeekFrom::Start(len - count))?;
+        let mut buffer = vec![0; count as usize];
+        file.read_exact(&mut buffer)?;
+        Ok(buffer)
+    };
+    Ok(tail(src)? == tail(dst)?)
+}
+
+/// Clone the whole file with `FICLONE`, sharing extents until either side changes.
+#[cfg(target_os = "linux")]
+fn try_reflink(reader: &File, writer: &File) -> io::Result<()> {
+    use std::os::unix::io::AsRawFd;
+
+    // SAFETY: both descriptors are open for the duration of the call
+    let result = unsafe { libc::ioctl(writer.as_raw_fd(), libc::FICLONE, reader.as_raw_fd()) };
+    if result == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn try_reflink(_reader: &File, _writer: &File) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "reflinks are not supported on this platform",
+    ))
+}
+
+/// Copy with `copy_file_range` so the kernel moves the data without a round trip
+/// through user space.
+///
+/// Returns `Ok(false)` when the kernel or filesystem cannot do it; the file
+/// offsets are then left where the buffered copy should continue.
+#[cfg(target_os = "linux")]
+fn copy_range(reader: &File, writer: &File, progress: &mut dyn FnMut(u64)) -> io::Result<bool> {
+    use std::os::unix::io::AsRawFd;
+
+    loop {
+        cancel::check()?;
+        // SAFETY: both descriptors are open and null offsets use the file positions
+        let written = unsafe {
+            libc::copy_file_range(
+                reader.as_raw_fd(),
+                std::ptr::null_mut(),
+                writer.as_raw_fd(),
+                std::ptr::null_mut(),
+                CHUNK_SIZE,
+                0,
+            )
+        };
+        match written {
+            0 => return Ok(true),
+            written if written > 0 => progress(written as u64),
+            _ => {
+                let err = io::Error::last_os_error();
+                return match err.raw_os_error() {
+                    // Unsupported here; a sandbox may also reject the syscall outright
+                    Some(
+                        libc::ENOSYS | libc::EXDEV | libc::EINVAL | libc::EOPNOTSUPP | libc::EPERM,
+                    ) => Ok(false),
+                    _ if err.kind() == io::ErrorKind::Interrupted => continue,
+                    _ => Err(err),
+                };
+            }
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn copy_range(_reader: &File, _writer: &File, _progress: &mut dyn FnMut(u64)) -> io::Result<bool> {
+    Ok(false)
+}
+
+fn copy_buffered(
+    reader: &mut File,
+    writer: &mut File,
+    progress: &mut dyn FnMut(u64),
+) -> io::Result<()> {
+    let mut buffer = vec![0; CHUNK_SIZE];
+    loop {
+        cancel::check()?;
+        let read = match reader.read(&mut buffer) {
+            Ok(0) => return Ok(()),
+            Ok(read) => read,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        writer.write_all(&buffer[..read])?;
+        progress(read as u64);
+    }
+}
+
+/// Attach the offending path to an IO error message.
+pub fn with_path(err: io::Error, path: &Path) -> io::Error {
+    io::Error::new(err.kind(), format!("{}: {err}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn resume_keeps_complete_files_and_continues_partial_ones() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let data: Vec<u8> = (0..100_000u32).map(|n| (n % 251) as u8).collect();
+        let modified = FileTime::from_unix_time(1_600_000_000, 0);
+        let files: Vec<FileJob> = [
+            "complete", "older", "stale", "partial", "garbled", "missing",
+        ]
+        .iter()
+        .map(|name| {
+            let src = dir.path().join(format!("src-{name}"));
+            fs::write(&src, &data).unwrap();
+            fs::set_permissions(&src, fs::Permissions::from_mode(0o644)).unwrap();
+            filetime::set_file_mtime(&src, modified).unwrap();
+            FileJob {
+                src,
+                dst: dir.path().join(format!("dst-{name}")),
+            }
+        })
+        .collect();
+        // A finished copy was written after the source changed; its mode shows whether it was rewritten
+        for job in &files[..2] {
+            fs::write(&job.dst, &data).unwrap();
+            fs::set_permissions(&job.dst, fs::Permissions::from_mode(0o600)).unwrap();
+        }
+        filetime::set_file_mtime(&files[1].dst, FileTime::from_unix_time(1_500_000_000, 0))
+            .unwrap();
+        // Files of the right size or with the wrong bytes were not written by the copy
+        fs::write(&files[2].dst, vec![0; data.len()]).unwrap();
+        fs::write(&files[3].dst, &data[..40_000]).unwrap();
+        fs::write(&files[4].dst, vec![0; 40_000]).unwrap();
+
+        let settings = CopySettings {
+            resume: true,
+            ..CopySettings::default()
+        };
+        let mut copied = 0;
+        copy_files(&files, settings, 1, &mut |event| {
+            if let CopyEvent::Bytes(bytes) = event {
+                copied += bytes;
+            }
+        })
+        .unwrap();
+
+        assert_eq!(copied, files.len() as u64 * data.len() as u64);
+        let mode = |job: &FileJob| fs::metadata(&job.dst).unwrap().permissions().mode() & 0o777;
+        assert_eq!(mode(&files[0]), 0o600);
+        assert_eq!(mode(&files[1]), 0o644);
+        for job in &files {
+            assert!(fs::read(&job.dst).unwrap() == data, "{}", job.dst.display());
+        }
+    }
+
+    #[test]
+    fn parallel_copy_reports_the_earliest_failure() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut files = Vec::new();
+        for index in 0..8 {
+            let src = dir.path().join(format!("src-{index}"));
+            // Jobs 2 and 5 have no source and must fail
+            if index != 2 && index != 5 {
+                fs::write(&src, vec![b'x'; 1024]).unwrap();
+            }
+            files.push(FileJob {
+                src,
+                dst: dir.path().join(format!("dst-{index}")),
+            });
+        }
+
+        let err = copy_files(&files, CopySettings::default(), 4, &mut |_| {})
+            .expect_err("missing sources should fail");
+
+        assert!(err.to_string().contains("src-2"), "{err}");
+        assert!(dir.path().join("dst-0").exists());
+        assert!(dir.path().join("dst-1").exists());
+    }
+
+    #[test]
+    fn parallel_copy_reports_every_byte() {
+        let dir = tempfile::tempdir().unwrap();
+        let files: Vec<FileJob> = (0..16)
+            .map(|index| {
+                let src = dir.path().join(format!("src-{index}"));
+                fs::write(&src, vec![b'x'; 100 * (index + 1)]).unwrap();
+                FileJob {
+                    src,
+                    dst: dir.path().join(format!("dst-{index}")),
+                }
+            })
+            .collect();
+
+        let (mut copied, mut done) = (0, 0);
+        copy_files(
+            &files,
+            CopySettings::default(),
+            3,
+            &mut |event| match event {
+                CopyEvent::Bytes(bytes) => copied += bytes,
+                CopyEvent::FileDone => done += 1,
+            },
+        )
+        .unwrap();
+
+        assert_eq!(copied, (1..=16).map(|n| 100 * n).sum::<u64>());
+        assert_eq!(done, 16);
+        for job in &files {
+            assert_eq!(fs::read(&job.src).unwrap(), fs::read(&job.dst).unwrap());
+        }
+    }
+}
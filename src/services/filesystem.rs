@@ -1,24 +1,24 @@
 use crate::error::{FugaError, FugaResult};
-use crate::fuga::{FileInfo, TargetType};
+use crate::fuga::{
+    ChecksumAlgorithm, ConflictPolicy, FileInfo, PathFilters, PriorDestination, SyncOptions,
+    SyncSummary, TargetType, TransferOptions, TransferOutcome,
+};
+use crate::services::cancel;
+use crate::services::checksum::verify_files;
+use crate::services::copy_engine::{copy_files, create_links, CopyPlan, CopySettings};
+use crate::services::metadata::preserve_tree;
+use crate::services::progress::{Footprint, ProgressDisplay};
+use crate::services::sync::{stamp_files, SyncPlan};
 use crate::traits::FileSystemService;
-use indicatif::{ProgressBar, ProgressStyle};
-use std::cell::RefCell;
+use std::collections::HashMap;
 use std::env;
-use std::fs::metadata;
+use std::fs::{self, metadata, symlink_metadata};
 #[cfg(unix)]
 use std::os::unix::fs::symlink;
 #[cfg(windows)]
 use std::os::windows::fs::{symlink_dir, symlink_file};
-use std::path::{Path, PathBuf};
-use std::rc::Rc;
-
-/// Progress bar template constants
-const PRIMARY_PROGRESS_BAR_TEMPLATE: &str =
-    "{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {bytes}/{total_bytes} ({eta})";
-const FALLBACK_PROGRESS_BAR_TEMPLATE: &str = "{bar:40} {bytes}/{total_bytes}";
-
-/// Type alias for file operation setup result
-type FileOperationSetup = (String, String, Box<dyn Fn(u64, u64, &str)>);
+use std::path::{Component, Path, PathBuf};
+use std::time::UNIX_EPOCH;
 
 /// Standard file system service implementation
 ///
@@ -26,11 +26,15 @@ type FileOperationSetup = (String, String, Box<dyn Fn(u64, u64, &str)>);
 /// path resolution, progress tracking, and symbolic link creation. Uses appropriate
 /// platform-specific APIs (Unix symlink vs Windows symlink_file/symlink_dir).
 #[derive(Default)]
-pub struct StandardFileSystemService;
+pub struct StandardFileSystemService {
+    progress: ProgressDisplay,
+}
 
 impl StandardFileSystemService {
     pub fn new() -> Self {
-        Self
+        Self {
+            progress: ProgressDisplay::new(),
+        }
     }
 
     fn pathbuf_to_string(path: PathBuf) -> FugaResult<String> {
@@ -41,39 +45,11 @@ impl StandardFileSystemService {
         })
     }
 
-    /// Create a progress bar with shared styling.
-    fn create_progress_bar(total: u64) -> ProgressBar {
-        let pbr = ProgressBar::new(total);
-
-        // Use a fallback template if the primary one fails
-        let style = ProgressStyle::default_bar()
-            .template(PRIMARY_PROGRESS_BAR_TEMPLATE)
-            .unwrap_or_else(|_| {
-                // Fallback to simple template if primary fails
-                ProgressStyle::default_bar()
-                    .template(FALLBACK_PROGRESS_BAR_TEMPLATE)
-                    .unwrap_or_else(|_| ProgressStyle::default_bar())
-            })
-            .progress_chars("#>-");
-
-        pbr.set_style(style);
-        pbr
-    }
-
-    /// Create shared progress bar update closure
-    fn create_progress_update_closure(
-        pbr: Rc<RefCell<Option<ProgressBar>>>,
-    ) -> impl Fn(u64, u64, &str) {
-        move |copied, total, item_name: &str| {
-            let mut pbr = pbr.borrow_mut();
-            let pbr = pbr.get_or_insert_with(|| Self::create_progress_bar(total));
-            pbr.set_position(copied);
-            pbr.set_message(item_name.to_string());
-        }
-    }
-
-    /// Common setup for copy/move operations: path resolution, duplicate check, and progress setup
-    fn setup_file_operation(&self, src: &str, dst: &str) -> FugaResult<FileOperationSetup> {
+    /// Common setup for copy/move operations: path resolution and duplicate check
+    ///
+    /// Also starts watching for Ctrl-C so a long copy can stop cleanly.
+    fn setup_file_operation(&self, src: &str, dst: &str) -> FugaResult<String> {
+        cancel::watch();
         let abs_src = self.get_abs_path(src)?;
         let abs_dst = self.get_abs_path(dst)?;
 
@@ -84,34 +60,313 @@ impl StandardFileSystemService {
             });
         }
 
-        let pbr = Rc::new(RefCell::new(None));
-        let update_pbr = Self::create_progress_update_closure(pbr);
-
-        Ok((abs_src, abs_dst, Box::new(update_pbr)))
+        Ok(abs_src)
     }
 
     /// Check if the path is an absolute path.
     fn is_abs_path(&self, path: &str) -> bool {
         Path::new(path).is_absolute()
     }
+
+    /// Check whether anything, including a dangling symlink, occupies the path.
+    fn path_occupied(path: &Path) -> bool {
+        symlink_metadata(path).is_ok()
+    }
+
+    /// Resolve an existing destination according to the conflict policy.
+    ///
+    /// Returns `None` when the target should be skipped, otherwise the outcome
+    /// describing where the operation should write.
+    fn resolve_conflict(
+        &self,
+        dst: &str,
+        policy: ConflictPolicy,
+    ) -> FugaResult<Option<TransferOutcome>> {
+        let proceed = |destination: String, backup: Option<String>| {
+            Ok(Some(TransferOutcome {
+                destination,
+                backup,
+                skipped: false,
+                cross_device: false,
+                prior: PriorDestination::Vacant,
+            }))
+        };
+
+        if !Self::path_occupied(Path::new(dst)) {
+            return proceed(dst.to_string(), None);
+        }
+
+        match policy {
+            ConflictPolicy::Overwrite => proceed(dst.to_string(), None),
+            ConflictPolicy::Skip => Ok(None),
+            ConflictPolicy::Rename => proceed(Self::next_free_name(dst)?, None),
+            ConflictPolicy::Backup => {
+                let backup = Self::backup_name(dst)?;
+                fs::rename(dst, &backup).map_err(|e| FugaError::from_io_error(e, dst))?;
+                proceed(dst.to_string(), Some(backup))
+            }
+            ConflictPolicy::Fail | ConflictPolicy::Ask => {
+                Err(FugaError::DestinationExists(dst.to_string()))
+            }
+        }
+    }
+
+    /// Find the first unused `name (N).ext` sibling of the given path.
+    fn next_free_name(dst: &str) -> FugaResult<String> {
+        let path = Path::new(dst);
+        let stem = path
+            .file_stem()
+            .map(|value| value.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let extension = path
+            .extension()
+            .map(|value| format!(".{}", value.to_string_lossy()))
+            .unwrap_or_default();
+
+        for index in 1.. {
+            let candidate = path.with_file_name(format!("{stem} ({index}){extension}"));
+            if !Self::path_occupied(&candidate) {
+                return Self::pathbuf_to_string(candidate);
+            }
+        }
+        unreachable!("an unbounded range always yields a free name")
+    }
+
+    /// Pick a backup name: `name~` first, then numbered `name.~N~` like `cp --backup`.
+    fn backup_name(dst: &str) -> FugaResult<String> {
+        let simple = PathBuf::from(format!("{dst}~"));
+        if !Self::path_occupied(&simple) {
+            return Self::pathbuf_to_string(simple);
+        }
+
+        for index in 1.. {
+            let candidate = PathBuf::from(format!("{dst}.~{index}~"));
+            if !Self::path_occupied(&candidate) {
+                return Self::pathbuf_to_string(candidate);
+            }
+        }
+        unreachable!("an unbounded range always yields a free name")
+    }
+
+    /// Copy an already resolved source onto an already resolved destination.
+    ///
+    /// Directories are merged into an existing destination directory. Returns
+    /// the plan, which lists exactly what the filters let through,
+    /// and what was at the destination before.
+    fn copy_resolved(
+        &self,
+        abs_src: String,
+        abs_dst: &str,
+        dst: &str,
+        action: &str,
+        options: &TransferOptions,
+    ) -> FugaResult<(CopyPlan, PriorDestination)> {
+        match self.get_file_type(&abs_src) {
+            TargetType::None => return Err(FugaError::FileNotFound(abs_src)),
+            TargetType::Other => {
+                return Err(FugaError::FileSystemError(format!(
+                    "{action} failed: {abs_src} is not a file, directory, or symbolic link"
+                )));
+            }
+            _ => {}
+        }
+
+        let failed = |e| operation_error(action, e);
+        let plan = CopyPlan::build(
+            Path::new(&abs_src),
+            Path::new(abs_dst),
+            &options.filters,
+            options.dereference,
+        )
+        .map_err(failed)?;
+        let prior = plan.prior_destination(Path::new(abs_dst));
+        plan.create_dirs().map_err(failed)?;
+        plan.create_links().map_err(failed)?;
+
+        let mut progress = self.progress.start_item(&abs_src, dst);
+        progress.set_total(plan.total_bytes);
+        let settings = CopySettings {
+            reflink: options.reflink,
+            resume: options.resume,
+        };
+        copy_files(&plan.files, settings, options.jobs, &mut |event| {
+            progress.record(event)
+        })
+        .map_err(failed)?;
+        progress.finish();
+        Ok((plan, prior))
+    }
+
+    /// Copied files of `plan` that differ from their source
+    fn mismatched_files(
+        plan: &CopyPlan,
+        algorithm: ChecksumAlgorithm,
+        action: &str,
+    ) -> FugaResult<Vec<String>> {
+        let mismatches =
+            verify_files(&plan.files, algorithm).map_err(|e| operation_error(action, e))?;
+        Ok(mismatches
+            .iter()
+            .map(|file| file.display().to_string())
+            .collect())
+    }
+
+    /// Hard-link a file, or recreate a directory and hard-link every file inside it like `cp -al`.
+    fn hard_link_tree(src: &Path, dst: &Path) -> std::io::Result<()> {
+        if !metadata(src)?.is_dir() {
+            return fs::hard_link(src, dst);
+        }
+
+        fs::create_dir(dst)?;
+        for entry in fs::read_dir(src)? {
+            let entry = entry?;
+            let target = dst.join(entry.file_name());
+            if entry.file_type()?.is_dir() {
+                Self::hard_link_tree(&entry.path(), &target)?;
+            } else {
+                // Symlinks inside the tree are linked themselves rather than followed
+                fs::hard_link(entry.path(), target)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether a move copies `src` wherever it goes: filtered directories are moved entry
+    /// by entry so excluded ones stay behind, and a rename cannot follow links
+    fn moved_by_copy(src_info: &fs::Metadata, options: &TransferOptions) -> bool {
+        let filtered = !options.filters.is_empty() && src_info.is_dir();
+        let follow = options.dereference && (src_info.is_dir() || src_info.is_symlink());
+        filtered || follow
+    }
+
+    /// Merge the directory `src` into the existing directory `dst` by renaming each
+    /// entry into place, then drop the emptied `src`
+    ///
+    /// Entries `dst` lacks are added to `created`; directories on both sides are
+    /// merged in turn, and anything else in the way is replaced, setting `replaced`.
+    fn rename_into(
+        src: &Path,
+        dst: &Path,
+        created: &mut Vec<String>,
+        replaced: &mut bool,
+    ) -> std::io::Result<()> {
+        for entry in Self::sorted_entries(src)? {
+            let target = dst.join(entry.file_name());
+            match symlink_metadata(&target) {
+                Ok(info) if info.is_dir() && entry.file_type()?.is_dir() => {
+                    Self::rename_into(&entry.path(), &target, created, replaced)?;
+                }
+                Ok(_) => {
+                    fs::rename(entry.path(), &target)?;
+                    *replaced = true;
+                }
+                Err(_) => {
+                    fs::rename(entry.path(), &target)?;
+                    created.push(target.display().to_string());
+                }
+            }
+        }
+        fs::remove_dir(src)
+    }
+
+    /// Append `entries` and what is below them in sorted, depth-first order,
+    /// going at most `depth` levels deep
+    ///
+    /// A subdirectory that cannot be listed only hides its own entries.
+    fn collect_tree(
+        entries: Vec<fs::DirEntry>,
+        depth: Option<usize>,
+        paths: &mut Vec<String>,
+    ) -> FugaResult<()> {
+        for entry in entries {
+            let path = entry.path();
+            paths.push(Self::pathbuf_to_string(path.clone())?);
+            let deeper = match depth {
+                Some(0 | 1) => continue,
+                depth => depth.map(|levels| levels - 1),
+            };
+            // The entry's own type, so links to directories are listed but not entered
+            if entry.file_type().is_ok_and(|kind| kind.is_dir()) {
+                if let Ok(children) = Self::sorted_entries(&path) {
+                    Self::collect_tree(children, deeper, paths)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn sorted_entries(dir: &Path) -> std::io::Result<Vec<fs::DirEntry>> {
+        let mut entries = fs::read_dir(dir)?.collect::<std::io::Result<Vec<_>>>()?;
+        entries.sort_by_key(|entry| entry.file_name());
+        Ok(entries)
+    }
+
+    /// Path to store in a symlink at `abs_dst` so that it resolves to `abs_src`.
+    ///
+    /// Both parents are canonicalized so symlinked directories along either
+    /// path do not make the relative path point somewhere else.
+    fn relative_link_target(abs_src: &str, abs_dst: &str) -> FugaResult<PathBuf> {
+        let canonical_parent = |path: &Path| -> FugaResult<PathBuf> {
+            match (path.parent(), path.file_name()) {
+                (Some(parent), Some(name)) => Ok(fs::canonicalize(parent)
+                    .map_err(|e| FugaError::from_io_error(e, &parent.to_string_lossy()))?
+                    .join(name)),
+                _ => Ok(path.to_path_buf()),
+            }
+        };
+
+        let source = canonical_parent(Path::new(abs_src))?;
+        let link = canonical_parent(Path::new(abs_dst))?;
+        let link_dir = link.parent().unwrap_or(&link);
+        Ok(relative_path(link_dir, &source).unwrap_or(source))
+    }
+
+    fn skipped(dst: &str) -> TransferOutcome {
+        TransferOutcome {
+            destination: dst.to_string(),
+            backup: None,
+            skipped: true,
+            cross_device: false,
+            prior: PriorDestination::Vacant,
+        }
+    }
 }
 
 impl FileSystemService for StandardFileSystemService {
     fn get_file_info(&self, path: &str) -> FugaResult<FileInfo> {
-        match metadata(path) {
-            Ok(metadata) => Ok(FileInfo {
-                exists: true,
-                is_file: metadata.is_file(),
-                is_dir: metadata.is_dir(),
-                name: Path::new(path)
-                    .file_name()
-                    .and_then(|n| n.to_str().map(|value| value.to_string())),
-            }),
+        match symlink_metadata(path) {
+            Ok(own) => {
+                let is_symlink = own.file_type().is_symlink();
+                // A link reports what it points to; a dangling one is neither
+                let pointed = match is_symlink {
+                    true => metadata(path).ok(),
+                    false => Some(own.clone()),
+                };
+                let shown = pointed.as_ref().unwrap_or(&own);
+                Ok(FileInfo {
+                    exists: true,
+                    is_file: pointed.as_ref().is_some_and(|info| info.is_file()),
+                    is_dir: pointed.as_ref().is_some_and(|info| info.is_dir()),
+                    is_symlink,
+                    size: shown.len(),
+                    modified: shown
+                        .modified()
+                        .ok()
+                        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+                        .map(|elapsed| elapsed.as_secs()),
+                    name: Path::new(path)
+                        .file_name()
+                        .and_then(|n| n.to_str().map(|value| value.to_string())),
+                })
+            }
             Err(e) => match e.kind() {
                 std::io::ErrorKind::NotFound => Ok(FileInfo {
                     exists: false,
                     is_file: false,
                     is_dir: false,
+                    is_symlink: false,
+                    size: 0,
+                    modified: None,
                     name: None,
                 }),
                 _ => Err(FugaError::from_io_error(e, path)),
@@ -134,84 +389,316 @@ impl FileSystemService for StandardFileSystemService {
 
     fn get_file_type(&self, path: &str) -> TargetType {
         match self.get_file_info(path) {
-            Ok(info) => {
-                if !info.exists {
-                    TargetType::None
-                } else if info.is_file {
-                    TargetType::File
-                } else {
-                    TargetType::Dir
-                }
-            }
+            Ok(info) => info.target_type(),
             Err(_) => TargetType::None,
         }
     }
 
-    fn copy_items(&self, src: &str, dst: &str) -> FugaResult<()> {
-        let (abs_src, abs_dst, update_pbr) = self.setup_file_operation(src, dst)?;
+    fn list_tree(&self, dir: &str, depth: Option<usize>) -> FugaResult<Vec<String>> {
+        let entries =
+            Self::sorted_entries(Path::new(dir)).map_err(|e| FugaError::from_io_error(e, dir))?;
+        let mut paths = Vec::new();
+        Self::collect_tree(entries, depth, &mut paths)?;
+        Ok(paths)
+    }
 
-        match self.get_file_type(&abs_src) {
-            TargetType::File => {
-                let mut options = fs_extra::file::CopyOptions::new();
-                options.overwrite = true;
-                let handle = |process_info: fs_extra::file::TransitProcess| {
-                    update_pbr(process_info.copied_bytes, process_info.total_bytes, dst);
-                };
-                fs_extra::file::copy_with_progress(abs_src, abs_dst, &options, handle)
-                    .map_err(|e| FugaError::FileSystemError(format!("Copy failed: {e}")))?;
+    fn check_readable(&self, path: &str) -> FugaResult<()> {
+        let result = if metadata(path)
+            .map_err(|e| FugaError::from_io_error(e, path))?
+            .is_dir()
+        {
+            fs::read_dir(path).map(|_| ())
+        } else {
+            fs::File::open(path).map(|_| ())
+        };
+        result.map_err(|e| FugaError::from_io_error(e, path))
+    }
+
+    fn create_dir(&self, path: &str) -> FugaResult<()> {
+        fs::create_dir_all(path).map_err(|e| FugaError::from_io_error(e, path))
+    }
+
+    fn check_writable_dir(&self, path: &str) -> FugaResult<()> {
+        let info = metadata(path).map_err(|e| FugaError::from_io_error(e, path))?;
+        if !info.is_dir() {
+            return Err(FugaError::OperationFailed(format!(
+                "Destination parent is not a directory: {path}"
+            )));
+        }
+
+        #[cfg(unix)]
+        {
+            let c_path = std::ffi::CString::new(path).map_err(|_| {
+                FugaError::OperationFailed(format!("Path contains a NUL byte: {path}"))
+            })?;
+            // SAFETY: `c_path` is a valid NUL-terminated string for the duration of the call
+            if unsafe { libc::access(c_path.as_ptr(), libc::W_OK | libc::X_OK) } != 0 {
+                return Err(FugaError::permission_error(
+                    path,
+                    std::io::Error::last_os_error(),
+                ));
             }
-            TargetType::Dir => {
-                let mut options = fs_extra::dir::CopyOptions::new();
-                options.overwrite = true;
-                options.copy_inside = true;
-                let handle = |process_info: fs_extra::dir::TransitProcess| {
-                    update_pbr(process_info.copied_bytes, process_info.total_bytes, dst);
-                    fs_extra::dir::TransitProcessResult::ContinueOrAbort
-                };
-                fs_extra::dir::copy_with_progress(abs_src, abs_dst, &options, handle)
-                    .map_err(|e| FugaError::FileSystemError(format!("Copy failed: {e}")))?;
+        }
+        #[cfg(not(unix))]
+        {
+            if info.permissions().readonly() {
+                return Err(FugaError::PermissionError {
+                    path: path.to_string(),
+                    message: "directory is read-only".to_string(),
+                });
             }
-            TargetType::None => {
-                return Err(FugaError::FileNotFound(abs_src));
+        }
+
+        Ok(())
+    }
+
+    fn check_same_filesystem(&self, path: &str, dir: &str) -> FugaResult<()> {
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+
+            let source = metadata(path).map_err(|e| FugaError::from_io_error(e, path))?;
+            let target = metadata(dir).map_err(|e| FugaError::from_io_error(e, dir))?;
+            if source.dev() != target.dev() {
+                return Err(FugaError::CrossDevice {
+                    source: path.to_string(),
+                    destination: dir.to_string(),
+                });
             }
         }
+        #[cfg(not(unix))]
+        {
+            // Without device ids the link attempt itself reports the problem
+            let _ = (path, dir);
+        }
+        Ok(())
+    }
 
+    fn begin_progress(
+        &self,
+        sources: &[(String, PathFilters)],
+        dereference: bool,
+    ) -> FugaResult<()> {
+        // Measuring a large batch takes a while, so Ctrl-C must already cancel it
+        cancel::watch();
+        let mut footprints = HashMap::new();
+        for (source, filters) in sources {
+            self.check_cancelled()?;
+            let abs_src = self.get_abs_path(source)?;
+            let footprint = Footprint::measure(Path::new(&abs_src), filters, dereference)?;
+            footprints.insert(abs_src, footprint);
+        }
+        self.progress.begin_batch(footprints);
         Ok(())
     }
 
-    fn move_items(&self, src: &str, dst: &str) -> FugaResult<()> {
-        let (abs_src, abs_dst, update_pbr) = self.setup_file_operation(src, dst)?;
+    fn end_progress(&self) {
+        self.progress.end_batch();
+    }
 
-        match self.get_file_type(&abs_src) {
-            TargetType::File => {
-                let mut options = fs_extra::file::CopyOptions::new();
-                options.overwrite = true;
-                let handle = |process_info: fs_extra::file::TransitProcess| {
-                    update_pbr(process_info.copied_bytes, process_info.total_bytes, dst);
-                };
-                fs_extra::file::move_file_with_progress(abs_src, abs_dst, &options, handle)
-                    .map_err(|e| FugaError::FileSystemError(format!("Move failed: {e}")))?;
+    fn check_cancelled(&self) -> FugaResult<()> {
+        match cancel::is_cancelled() {
+            true => Err(FugaError::Cancelled {
+                remaining: Vec::new(),
+            }),
+            false => Ok(()),
+        }
+    }
+
+    fn remove_items(&self, path: &str) -> FugaResult<()> {
+        let info = symlink_metadata(path).map_err(|e| FugaError::from_io_error(e, path))?;
+        let result = if info.is_dir() {
+            fs::remove_dir_all(path)
+        } else {
+            #[cfg(windows)]
+            {
+                // Directory symlinks on Windows must be removed as directories
+                if info.file_type().is_symlink() && metadata(path).is_ok_and(|m| m.is_dir()) {
+                    fs::remove_dir(path)
+                } else {
+                    fs::remove_file(path)
+                }
             }
-            TargetType::Dir => {
-                let mut options = fs_extra::dir::CopyOptions::new();
-                options.overwrite = true;
-                options.copy_inside = true;
-                let handle = |process_info: fs_extra::dir::TransitProcess| {
-                    update_pbr(process_info.copied_bytes, process_info.total_bytes, dst);
-                    fs_extra::dir::TransitProcessResult::ContinueOrAbort
-                };
-                fs_extra::dir::move_dir_with_progress(abs_src, abs_dst, &options, handle)
+            #[cfg(not(windows))]
+            {
+                fs::remove_file(path)
+            }
+        };
+        result.map_err(|e| FugaError::from_io_error(e, path))
+    }
+
+    fn copy_items(
+        &self,
+        src: &str,
+        dst: &str,
+        options: &TransferOptions,
+    ) -> FugaResult<TransferOutcome> {
+        let abs_src = self.setup_file_operation(src, dst)?;
+        let Some(outcome) = self.resolve_conflict(dst, options.on_conflict)? else {
+            return Ok(Self::skipped(dst));
+        };
+        let dst = outcome.destination.as_str();
+        let abs_dst = self.get_abs_path(dst)?;
+        let fresh = !Self::path_occupied(Path::new(&abs_dst));
+
+        let result = self
+            .copy_resolved(abs_src.clone(), &abs_dst, dst, "Copy", options)
+            .and_then(|(_, prior)| {
+                preserve_tree(Path::new(&abs_src), Path::new(&abs_dst), &options.preserve)?;
+                Ok(prior)
+            });
+        match result {
+            Ok(prior) => Ok(TransferOutcome { prior, ..outcome }),
+            Err(err) => {
+                if options.cleanup_on_failure && fresh && Self::path_occupied(Path::new(&abs_dst)) {
+                    // Best effort: the copy error is more useful than a cleanup error
+                    let _ = self.remove_items(&abs_dst);
+                }
+                Err(err)
+            }
+        }
+    }
+
+    fn verify_copy(
+        &self,
+        src: &str,
+        dst: &str,
+        options: &TransferOptions,
+    ) -> FugaResult<Vec<String>> {
+        let Some(algorithm) = options.verify else {
+            return Ok(Vec::new());
+        };
+        let abs_src = self.get_abs_path(src)?;
+        let abs_dst = self.get_abs_path(dst)?;
+        // The same walk as the copy, so exactly the files it wrote are checked
+        let plan = CopyPlan::build(
+            Path::new(&abs_src),
+            Path::new(&abs_dst),
+            &options.filters,
+            options.dereference,
+        )
+        .map_err(|e| operation_error("Verification", e))?;
+        Self::mismatched_files(&plan, algorithm, "Verification")
+    }
+
+    fn move_items(
+        &self,
+        src: &str,
+        dst: &str,
+        options: &TransferOptions,
+    ) -> FugaResult<TransferOutcome> {
+        let abs_src = self.setup_file_operation(src, dst)?;
+        let Some(outcome) = self.resolve_conflict(dst, options.on_conflict)? else {
+            return Ok(Self::skipped(dst));
+        };
+        let dst = outcome.destination.as_str();
+        let abs_dst = self.get_abs_path(dst)?;
+
+        if self.get_file_type(&abs_src) == TargetType::None {
+            return Err(FugaError::FileNotFound(abs_src));
+        }
+
+        let src_info =
+            symlink_metadata(&abs_src).map_err(|e| FugaError::from_io_error(e, &abs_src))?;
+        let filtered = !options.filters.is_empty() && src_info.is_dir();
+        if !Self::moved_by_copy(&src_info, options) {
+            // Renaming over a file or link replaces it; an empty directory has nothing to lose
+            let prior = match symlink_metadata(&abs_dst) {
+                Ok(info) if !info.is_dir() => PriorDestination::Overwritten,
+                _ => PriorDestination::Vacant,
+            };
+            // A rename is atomic and instant on the same filesystem, whatever the size
+            match fs::rename(&abs_src, &abs_dst) {
+                Ok(()) => return Ok(TransferOutcome { prior, ..outcome }),
+                // Only a move to another filesystem falls back to copying
+                Err(e) if e.kind() == std::io::ErrorKind::CrossesDevices => {}
+                // Overwriting a directory that has entries merges into it, still by renaming
+                Err(e) if e.kind() == std::io::ErrorKind::DirectoryNotEmpty => {
+                    let mut created = Vec::new();
+                    let mut replaced = false;
+                    Self::rename_into(
+                        Path::new(&abs_src),
+                        Path::new(&abs_dst),
+                        &mut created,
+                        &mut replaced,
+                    )
                     .map_err(|e| FugaError::FileSystemError(format!("Move failed: {e}")))?;
+                    let prior = match replaced {
+                        true => PriorDestination::Overwritten,
+                        false => PriorDestination::Merged(created),
+                    };
+                    return Ok(TransferOutcome { prior, ..outcome });
+                }
+                Err(e) => {
+                    return Err(FugaError::FileSystemError(format!("Move failed: {e}")));
+                }
             }
-            TargetType::None => {
-                return Err(FugaError::FileNotFound(abs_src));
+        }
+
+        // Copy (and verify when asked), carry over the requested metadata, and only then drop the source
+        let fresh = !Self::path_occupied(Path::new(&abs_dst));
+        let (plan, prior) =
+            match self.copy_resolved(abs_src.clone(), &abs_dst, dst, "Move", options) {
+                Ok(copied) => copied,
+                Err(err) => {
+                    // A cancelled move did not happen; the source is untouched, so drop the partial copy
+                    if err.is_cancelled() && fresh && Self::path_occupied(Path::new(&abs_dst)) {
+                        let _ = self.remove_items(&abs_dst);
+                    }
+                    return Err(err);
+                }
+            };
+        if let Some(algorithm) = options.verify {
+            let files = Self::mismatched_files(&plan, algorithm, "Move")?;
+            if !files.is_empty() {
+                return Err(FugaError::ChecksumMismatch {
+                    destination: abs_dst,
+                    files,
+                    outcome: "The source was left in place and the copy was kept.".to_string(),
+                });
+            }
+        }
+        preserve_tree(Path::new(&abs_src), Path::new(&abs_dst), &options.preserve)?;
+        if filtered {
+            let sources = plan.files.iter().map(|job| &job.src);
+            for src in sources.chain(plan.links.iter().map(|link| &link.src)) {
+                self.remove_items(&src.display().to_string())?;
+            }
+            // Directories still holding excluded entries are not empty and stay
+            for dir in plan.src_dirs.iter().rev() {
+                let _ = fs::remove_dir(dir);
             }
+        } else {
+            self.remove_items(&abs_src)?;
         }
 
-        Ok(())
+        Ok(TransferOutcome {
+            cross_device: true,
+            prior,
+            ..outcome
+        })
     }
 
-    fn link_items(&self, src: &str, dst: &str) -> FugaResult<()> {
+    fn move_copies(&self, src: &str, dst: &str, options: &TransferOptions) -> FugaResult<bool> {
+        let src_info = symlink_metadata(src).map_err(|e| FugaError::from_io_error(e, src))?;
+        if Self::moved_by_copy(&src_info, options) {
+            return Ok(true);
+        }
+        // A rename, or a merge renaming entry by entry, only works within one filesystem
+        let abs_dst = self.get_abs_path(dst)?;
+        let parent = Path::new(&abs_dst)
+            .parent()
+            .map(|parent| parent.to_string_lossy().into_owned())
+            .unwrap_or(abs_dst);
+        Ok(self.check_same_filesystem(src, &parent).is_err())
+    }
+
+    fn link_items(
+        &self,
+        src: &str,
+        dst: &str,
+        options: &TransferOptions,
+    ) -> FugaResult<TransferOutcome> {
         let abs_src = self.get_abs_path(src)?;
         let abs_dst = self.get_abs_path(dst)?;
 
@@ -222,27 +709,74 @@ impl FileSystemService for StandardFileSystemService {
             });
         }
 
+        if self.get_file_type(&abs_src) == TargetType::None {
+            return Err(FugaError::FileNotFound(abs_src));
+        }
+
+        let Some(outcome) = self.resolve_conflict(dst, options.on_conflict)? else {
+            return Ok(Self::skipped(dst));
+        };
+        let abs_dst = self.get_abs_path(&outcome.destination)?;
+
+        let mut prior = PriorDestination::Vacant;
+        if Self::path_occupied(Path::new(&abs_dst)) {
+            // Only the overwrite policy reaches here with an occupied destination
+            let existing = symlink_metadata(&abs_dst)?;
+            if existing.is_dir() {
+                return Err(FugaError::OperationFailed(format!(
+                    "Refusing to replace directory {abs_dst} with a link. Use --on-conflict rename or backup instead."
+                )));
+            }
+            fs::remove_file(&abs_dst).map_err(|e| FugaError::from_io_error(e, &abs_dst))?;
+            prior = PriorDestination::Overwritten;
+        }
+        let outcome = TransferOutcome { prior, ..outcome };
+
+        if options.hard_link {
+            let result = Self::hard_link_tree(Path::new(&abs_src), Path::new(&abs_dst));
+            if let Err(err) = result {
+                if options.cleanup_on_failure && Self::path_occupied(Path::new(&abs_dst)) {
+                    // Best effort: the link error is more useful than a cleanup error
+                    let _ = self.remove_items(&abs_dst);
+                }
+                return Err(match err.kind() {
+                    std::io::ErrorKind::CrossesDevices => FugaError::CrossDevice {
+                        source: abs_src,
+                        destination: abs_dst,
+                    },
+                    _ => FugaError::FileSystemError(format!("Hard link failed: {err}")),
+                });
+            }
+            return Ok(outcome);
+        }
+
         match self.get_file_type(&abs_src) {
             TargetType::None => {
                 return Err(FugaError::FileNotFound(abs_src));
             }
             _ => {
+                let link_target = if options.relative_link {
+                    Self::relative_link_target(&abs_src, &abs_dst)?
+                } else {
+                    PathBuf::from(&abs_src)
+                };
+
                 #[cfg(unix)]
                 {
-                    symlink(&abs_src, &abs_dst)
+                    symlink(&link_target, &abs_dst)
                         .map_err(|e| FugaError::FileSystemError(format!("Link failed: {e}")))?;
                 }
                 #[cfg(windows)]
                 {
                     // On Windows, use appropriate symlink function based on target type
-                    match self.get_file_type(&abs_src) {
-                        TargetType::Dir => {
-                            symlink_dir(&abs_src, &abs_dst).map_err(|e| {
+                    match metadata(&abs_src).is_ok_and(|info| info.is_dir()) {
+                        true => {
+                            symlink_dir(&link_target, &abs_dst).map_err(|e| {
                                 FugaError::FileSystemError(format!("Link failed: {e}"))
                             })?;
                         }
-                        _ => {
-                            symlink_file(&abs_src, &abs_dst).map_err(|e| {
+                        false => {
+                            symlink_file(&link_target, &abs_dst).map_err(|e| {
                                 FugaError::FileSystemError(format!("Link failed: {e}"))
                             })?;
                         }
@@ -251,6 +785,221 @@ impl FileSystemService for StandardFileSystemService {
             }
         }
 
-        Ok(())
+        Ok(outcome)
+    }
+
+    fn sync_items(&self, src: &str, dst: &str, options: &SyncOptions) -> FugaResult<SyncSummary> {
+        let abs_src = self.setup_file_operation(src, dst)?;
+        let abs_dst = self.get_abs_path(dst)?;
+        if self.get_file_type(&abs_src) == TargetType::None {
+            return Err(FugaError::FileNotFound(abs_src));
+        }
+
+        let failed = |e| operation_error("Sync", e);
+        let plan =
+            SyncPlan::build(Path::new(&abs_src), Path::new(&abs_dst), options).map_err(failed)?;
+        let summary = SyncSummary {
+            changes: plan.changes.clone(),
+            unchanged: plan.unchanged,
+        };
+        if options.dry_run {
+            return Ok(summary);
+        }
+
+        for path in &plan.remove {
+            self.remove_items(&path.to_string_lossy())?;
+        }
+        for dir in &plan.dirs {
+            fs::create_dir_all(dir).map_err(failed)?;
+        }
+
+        let mut progress = self.progress.start_item(&abs_src, dst);
+        progress.set_total(plan.total_bytes);
+        let settings = CopySettings {
+            reflink: options.reflink,
+            resume: false,
+        };
+        copy_files(&plan.files, settings, options.jobs, &mut |event| {
+            progress.record(event)
+        })
+        .map_err(failed)?;
+        progress.finish();
+
+        stamp_files(&plan.files).map_err(failed)?;
+        create_links(&plan.links).map_err(failed)?;
+        Ok(summary)
+    }
+}
+
+/// Report an IO failure of a copy-like operation, keeping cancellations recognizable
+fn operation_error(action: &str, err: std::io::Error) -> FugaError {
+    match cancel::is_cancellation(&err) {
+        true => FugaError::Cancelled {
+            remaining: Vec::new(),
+        },
+        false => FugaError::FileSystemError(format!("{action} failed: {err}")),
+    }
+}
+
+/// Shortest relative path from the directory `base` to `target`.
+///
+/// Both paths must be absolute and normalized. Returns `None` when they do not
+/// share a root, such as different drives on Windows.
+fn relative_path(base: &Path, target: &Path) -> Option<PathBuf> {
+    let base: Vec<Component> = base.components().collect();
+    let target: Vec<Component> = target.components().collect();
+    if base.first() != target.first() {
+        return None;
+    }
+
+    let common = base
+        .iter()
+        .zip(&target)
+        .take_while(|(left, right)| left == right)
+        .count();
+    let mut relative = PathBuf::new();
+    for _ in common..base.len() {
+        relative.push(Component::ParentDir);
+    }
+    for component in &target[common..] {
+        relative.push(component);
+    }
+    if relative.as_os_str().is_empty() {
+        relative.push(Component::CurDir);
+    }
+    Some(relative)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn relative_path_walks_up_to_the_common_ancestor() {
+        let relative = |base: &str, target: &str| relative_path(Path::new(base), Path::new(target));
+
+        assert_eq!(
+            relative("/repo/docs", "/repo/src/main.rs"),
+            Some(PathBuf::from("../src/main.rs"))
+        );
+        assert_eq!(
+            relative("/repo", "/repo/src/main.rs"),
+            Some(PathBuf::from("src/main.rs"))
+        );
+        assert_eq!(
+            relative("/repo/a/b", "/other"),
+            Some(PathBuf::from("../../../other"))
+        );
+        assert_eq!(relative("/repo", "/repo"), Some(PathBuf::from(".")));
+    }
+
+    #[test]
+    fn list_tree_stops_at_the_requested_depth() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("a/b")).unwrap();
+        fs::write(dir.path().join("a/b/deep.log"), "").unwrap();
+        fs::write(dir.path().join("top.log"), "").unwrap();
+        let root = dir.path().to_str().unwrap();
+        let relative = |paths: Vec<String>| -> Vec<String> {
+            paths
+                .iter()
+                .map(|path| path[root.len() + 1..].to_string())
+                .collect()
+        };
+
+        let service = StandardFileSystemService::new();
+        assert_eq!(
+            relative(service.list_tree(root, Some(1)).unwrap()),
+            ["a", "top.log"]
+        );
+        assert_eq!(
+            relative(service.list_tree(root, Some(2)).unwrap()),
+            ["a", "a/b", "top.log"]
+        );
+        assert_eq!(
+            relative(service.list_tree(root, None).unwrap()),
+            ["a", "a/b", "a/b/deep.log", "top.log"]
+        );
+        assert!(service.list_tree(&format!("{root}/missing"), None).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn list_tree_skips_directories_it_cannot_read() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let locked = dir.path().join("locked");
+        fs::create_dir(&locked).unwrap();
+        fs::write(locked.join("hidden"), "").unwrap();
+        fs::write(dir.path().join("open"), "").unwrap();
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o000)).unwrap();
+
+        let listed = StandardFileSystemService::new().list_tree(dir.path().to_str().unwrap(), None);
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o755)).unwrap();
+
+        // Root can still read the directory, so only its siblings are certain
+        let listed = listed.unwrap();
+        assert!(listed.contains(&locked.display().to_string()));
+        assert!(listed.contains(&dir.path().join("open").display().to_string()));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn rename_into_merges_without_copying() {
+        use std::os::unix::fs::MetadataExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let (src, dst) = (dir.path().join("src"), dir.path().join("dst"));
+        fs::create_dir_all(src.join("sub")).unwrap();
+        fs::create_dir_all(dst.join("sub")).unwrap();
+        fs::write(src.join("new.txt"), "new").unwrap();
+        fs::write(src.join("sub/deep.txt"), "deep").unwrap();
+        fs::write(dst.join("sub/other.txt"), "other").unwrap();
+        let inode = fs::metadata(src.join("sub/deep.txt")).unwrap().ino();
+
+        let mut created = Vec::new();
+        let mut replaced = false;
+        StandardFileSystemService::rename_into(&src, &dst, &mut created, &mut replaced).unwrap();
+
+        assert!(!src.exists());
+        assert!(!replaced);
+        assert_eq!(
+            created,
+            [
+                dst.join("new.txt").display().to_string(),
+                dst.join("sub/deep.txt").display().to_string()
+            ]
+        );
+        assert_eq!(fs::metadata(dst.join("sub/deep.txt")).unwrap().ino(), inode);
+        assert_eq!(
+            fs::read_to_string(dst.join("sub/other.txt")).unwrap(),
+            "other"
+        );
+    }
+
+    #[test]
+    fn verify_copy_reports_files_that_changed_after_the_copy() {
+        let dir = tempfile::tempdir().unwrap();
+        let (src, dst) = (dir.path().join("src"), dir.path().join("dst"));
+        fs::create_dir_all(src.join("sub")).unwrap();
+        fs::write(src.join("a.txt"), "a").unwrap();
+        fs::write(src.join("sub/b.txt"), "b").unwrap();
+        let (src, dst) = (src.to_str().unwrap(), dst.to_str().unwrap());
+
+        let service = StandardFileSystemService::new();
+        let options = TransferOptions {
+            verify: Some(ChecksumAlgorithm::Fast),
+            ..TransferOptions::default()
+        };
+        service.copy_items(src, dst, &options).unwrap();
+        assert!(service.verify_copy(src, dst, &options).unwrap().is_empty());
+
+        let bad = Path::new(dst).join("sub/b.txt");
+        fs::write(&bad, "x").unwrap();
+        assert_eq!(
+            service.verify_copy(src, dst, &options).unwrap(),
+            [bad.display().to_string()]
+        );
     }
 }
@@ -0,0 +1,230 @@
+use crate::fuga::PathFilters;
+use globset::{GlobBuilder, GlobMatcher};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::Match;
+use std::ffi::OsStr;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+/// Check that a `--exclude`/`--include` pattern is a valid glob
+pub fn parse_pattern(value: &str) -> Result<String, String> {
+    Pattern::new(value)
+        .map(|_| value.to_string())
+        .map_err(|e| e.to_string())
+}
+
+/// One compiled `--exclude` or `--include` pattern
+struct Pattern {
+    matcher: GlobMatcher,
+    /// Match the path relative to the marked directory instead of the entry name
+    by_path: bool,
+    /// A trailing `/` limits the pattern to directories
+    dir_only: bool,
+}
+
+impl Pattern {
+    fn new(value: &str) -> io::Result<Self> {
+        let dir_only = value.ends_with('/');
+        let trimmed = value.trim_end_matches('/');
+        let by_path = trimmed.contains('/');
+        let glob = GlobBuilder::new(trimmed.trim_start_matches('/'))
+            .literal_separator(true)
+            .build()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e.to_string()))?;
+        Ok(Self {
+            matcher: glob.compile_matcher(),
+            by_path,
+            dir_only,
+        })
+    }
+
+    fn matches(&self, name: &OsStr, rel: &Path, is_dir: bool) -> bool {
+        if self.dir_only && !is_dir {
+            return false;
+        }
+        match self.by_path {
+            true => self.matcher.is_match(rel),
+            false => self.matcher.is_match(name),
+        }
+    }
+}
+
+/// Compiled [`PathFilters`] for walking one marked directory
+pub struct TreeFilter {
+    exclude: Vec<Pattern>,
+    include: Vec<Pattern>,
+    respect_gitignore: bool,
+}
+
+impl TreeFilter {
+    pub fn new(filters: &PathFilters) -> io::Result<Self> {
+        let compile = |patterns: &[String]| {
+            patterns
+                .iter()
+                .map(|pattern| Pattern::new(pattern))
+                .collect::<io::Result<Vec<_>>>()
+        };
+        Ok(Self {
+            exclude: compile(&filters.exclude)?,
+            include: compile(&filters.include)?,
+            respect_gitignore: filters.respect_gitignore,
+        })
+    }
+
+    /// Filter state for the marked directory `root` itself.
+    ///
+    /// With `.gitignore` support, the rules of enclosing directories up to the
+    /// repository root apply as well, so marking a subdirectory behaves the
+    /// same as copying it from within the repository.
+    pub fn scope(&self, root: &Path) -> Scope<'_> {
+        let mut ignores = Vec::new();
+        if self.respect_gitignore {
+            let mut ancestors: Vec<&Path> = Vec::new();
+            for dir in root.ancestors().skip(1) {
+                ancestors.push(dir);
+                if dir.join(".git").exists() {
+                    break;
+                }
+            }
+            // Only a repository gives the enclosing rules any meaning
+            if ancestors
+                .last()
+                .is_some_and(|dir| dir.join(".git").exists())
+            {
+                ignores.extend(ancestors.iter().rev().filter_map(|dir| load_gitignore(dir)));
+            }
+            ignores.extend(load_gitignore(root));
+        }
+        Scope {
+            filter: self,
+            rel: PathBuf::new(),
+            ignores,
+        }
+    }
+}
+
+/// Filter state for one directory of the walk
+pub struct Scope<'a> {
+    filter: &'a TreeFilter,
+    /// This directory relative to the marked directory
+    rel: PathBuf,
+    /// `.gitignore` rules in effect, outermost first
+    ignores: Vec<Rc<Gitignore>>,
+}
+
+impl Scope<'_> {
+    /// Whether the entry `path` of this directory is part of the operation
+    pub fn allows(&self, path: &Path, is_dir: bool) -> bool {
+        let Some(name) = path.file_name() else {
+            return true;
+        };
+        let rel = self.rel.join(name);
+        let matches = |patterns: &[Pattern]| {
+            patterns
+                .iter()
+                .any(|pattern| pattern.matches(name, &rel, is_dir))
+        };
+
+        if matches(&self.filter.include) {
+            return true;
+        }
+        if matches(&self.filter.exclude) {
+            return false;
+        }
+        if self.filter.respect_gitignore {
+            if name == ".git" {
+                return false;
+            }
+            // The innermost file with an opinion decides, as in git
+            for ignore in self.ignores.iter().rev() {
+                match ignore.matched(path, is_dir) {
+                    Match::Ignore(_) => return false,
+                    Match::Whitelist(_) => return true,
+                    Match::None => {}
+                }
+            }
+        }
+        true
+    }
+
+    /// Filter state for the subdirectory `dir` of this directory
+    pub fn child(&self, dir: &Path) -> Self {
+        let mut ignores = self.ignores.clone();
+        if self.filter.respect_gitignore {
+            ignores.extend(load_gitignore(dir));
+        }
+        Scope {
+            filter: self.filter,
+            rel: self.rel.join(dir.file_name().unwrap_or_default()),
+            ignores,
+        }
+    }
+}
+
+fn load_gitignore(dir: &Path) -> Option<Rc<Gitignore>> {
+    let file = dir.join(".gitignore");
+    if !file.is_file() {
+        return None;
+    }
+    let mut builder = GitignoreBuilder::new(dir);
+    // Like git, unreadable lines or files are skipped rather than fatal
+    let _ = builder.add(file);
+    builder.build().ok().map(Rc::new)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn filter(exclude: &[&str], include: &[&str], respect_gitignore: bool) -> TreeFilter {
+        TreeFilter::new(&PathFilters {
+            exclude: exclude.iter().map(|s| s.to_string()).collect(),
+            include: include.iter().map(|s| s.to_string()).collect(),
+            respect_gitignore,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn name_patterns_match_at_any_depth_and_path_patterns_from_the_root() {
+        let filter = filter(
+            &["node_modules", "/docs/*.pdf", "cache/"],
+            &["keep.pdf"],
+            false,
+        );
+        let root = Path::new("/project");
+        let scope = filter.scope(root);
+        let docs = scope.child(&root.join("docs"));
+        let nested = docs.child(&root.join("docs/api"));
+
+        assert!(!nested.allows(&root.join("docs/api/node_modules"), true));
+        assert!(!docs.allows(&root.join("docs/manual.pdf"), false));
+        assert!(docs.allows(&root.join("docs/keep.pdf"), false));
+        assert!(nested.allows(&root.join("docs/api/manual.pdf"), false));
+        assert!(!scope.allows(&root.join("cache"), true));
+        assert!(scope.allows(&root.join("cache"), false));
+    }
+
+    #[test]
+    fn gitignore_rules_apply_per_directory_and_skip_git() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("repo");
+        fs::create_dir_all(root.join(".git")).unwrap();
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join(".gitignore"), "target/\n*.log\n").unwrap();
+        fs::write(root.join("src/.gitignore"), "!important.log\n").unwrap();
+
+        let filter = filter(&[], &[], true);
+        let scope = filter.scope(&root);
+        let src = scope.child(&root.join("src"));
+
+        assert!(!scope.allows(&root.join(".git"), true));
+        assert!(!scope.allows(&root.join("target"), true));
+        assert!(!scope.allows(&root.join("debug.log"), false));
+        assert!(scope.allows(&root.join("Cargo.toml"), false));
+        assert!(src.allows(&root.join("src/important.log"), false));
+        assert!(!src.allows(&root.join("src/other.log"), false));
+    }
+}
@@ -0,0 +1,108 @@
+use crate::error::{FugaError, FugaResult};
+use crate::fuga::PreserveOptions;
+use filetime::FileTime;
+use std::fs::{self, Metadata};
+use std::path::Path;
+
+/// Copy the requested metadata from `src` onto the already copied `dst`.
+///
+/// Directories are walked depth-first and updated after their contents, so
+/// their timestamps are not disturbed again by writes to the children.
+/// Entries that do not exist under `dst` are ignored.
+pub fn preserve_tree(src: &Path, dst: &Path, preserve: &PreserveOptions) -> FugaResult<()> {
+    if !preserve.any() {
+        return Ok(());
+    }
+
+    // Recreated links have no metadata of their own worth keeping, and changing
+    // it would change what they point to instead
+    match fs::symlink_metadata(dst) {
+        Ok(own) if !own.is_symlink() => {}
+        _ => return Ok(()),
+    }
+    let info =
+        fs::metadata(src).map_err(|e| FugaError::from_io_error(e, &src.display().to_string()))?;
+
+    if info.is_dir() {
+        let entries = fs::read_dir(src)
+            .map_err(|e| FugaError::from_io_error(e, &src.display().to_string()))?;
+        for entry in entries {
+            let entry = entry?;
+            preserve_tree(&entry.path(), &dst.join(entry.file_name()), preserve)?;
+        }
+    }
+
+    apply(src, dst, &info, preserve)
+}
+
+/// Apply metadata to a single path: ownership first because `chown` may clear
+/// set-id bits, extended attributes while the file is still writable, then the
+/// mode, and timestamps last since every other change may touch them.
+fn apply(src: &Path, dst: &Path, info: &Metadata, preserve: &PreserveOptions) -> FugaResult<()> {
+    if preserve.ownership {
+        set_ownership(dst, info)?;
+    }
+    if preserve.xattr {
+        copy_xattrs(src, dst)?;
+    }
+    if preserve.mode {
+        fs::set_permissions(dst, info.permissions()).map_err(|e| preserve_error("mode", dst, e))?;
+    }
+    if preserve.timestamps {
+        filetime::set_file_times(
+            dst,
+            FileTime::from_last_access_time(info),
+            FileTime::from_last_modification_time(info),
+        )
+        .map_err(|e| preserve_error("timestamps", dst, e))?;
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn set_ownership(dst: &Path, info: &Metadata) -> FugaResult<()> {
+    use std::os::unix::fs::MetadataExt;
+
+    std::os::unix::fs::chown(dst, Some(info.uid()), Some(info.gid()))
+        .map_err(|e| preserve_error("ownership", dst, e))
+}
+
+#[cfg(not(unix))]
+fn set_ownership(_dst: &Path, _info: &Metadata) -> FugaResult<()> {
+    Ok(())
+}
+
+#[cfg(unix)]
+fn copy_xattrs(src: &Path, dst: &Path) -> FugaResult<()> {
+    let names = match xattr::list(src) {
+        Ok(names) => names,
+        // Filesystems without xattr support simply have nothing to carry over
+        Err(e) if e.kind() == std::io::ErrorKind::Unsupported => return Ok(()),
+        Err(e) => return Err(preserve_error("extended attributes", src, e)),
+    };
+
+    for name in names {
+        let value =
+            xattr::get(src, &name).map_err(|e| preserve_error("extended attributes", src, e))?;
+        if let Some(value) = value {
+            match xattr::set(dst, &name, &value) {
+                Ok(()) => {}
+                Err(e) if e.kind() == std::io::ErrorKind::Unsupported => return Ok(()),
+                Err(e) => return Err(preserve_error("extended attributes", dst, e)),
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn copy_xattrs(_src: &Path, _dst: &Path) -> FugaResult<()> {
+    Ok(())
+}
+
+fn preserve_error(what: &str, path: &Path, err: std::io::Error) -> FugaError {
+    FugaError::FileSystemError(format!(
+        "Failed to preserve {what} on {}: {err}",
+        path.display()
+    ))
+}
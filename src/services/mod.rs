@@ -1,5 +1,12 @@
+pub mod cancel;
+pub mod checksum;
+pub mod copy_engine;
 pub mod filesystem;
+pub mod filter;
+pub mod metadata;
 pub mod path;
+pub mod progress;
+pub mod sync;
 
 pub use filesystem::StandardFileSystemService;
 pub use path::StandardPathService;
@@ -0,0 +1,430 @@
+use crate::error::{FugaError, FugaResult};
+use crate::fuga::PathFilters;
+use crate::services::copy_engine::{CopyEvent, CopyPlan};
+use indicatif::{HumanBytes, HumanDuration, MultiProgress, ProgressBar, ProgressStyle};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::io::{self, IsTerminal};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// Progress bar template constants
+const PRIMARY_PROGRESS_BAR_TEMPLATE: &str =
+    "{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {bytes}/{total_bytes} ({eta})";
+const FALLBACK_PROGRESS_BAR_TEMPLATE: &str = "{bar:40} {bytes}/{total_bytes}";
+const OVERALL_PROGRESS_BAR_TEMPLATE: &str = "{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {bytes}/{total_bytes} {binary_bytes_per_sec} ETA {eta} {msg}";
+const ITEM_PROGRESS_BAR_TEMPLATE: &str = "  [{bar:40.white/blue}] {bytes}/{total_bytes} {msg}";
+
+/// Minimum time between two plain progress lines
+const PLAIN_LINE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Bytes and regular files that copying a target writes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Footprint {
+    pub bytes: u64,
+    pub files: u64,
+}
+
+impl Footprint {
+    /// Measure a file or directory tree as the copy reads it
+    ///
+    /// This is the walk the copy plans with, so entries `filters` leave out add
+    /// nothing, and neither do symbolic links without `dereference`, as they are
+    /// recreated rather than read. Errors name the entry that could not be read,
+    /// not the root of the tree.
+    pub fn measure(path: &Path, filters: &PathFilters, dereference: bool) -> FugaResult<Self> {
+        let plan = CopyPlan::build(path, path, filters, dereference).map_err(|e| {
+            FugaError::FileSystemError(format!("Could not measure {}: {e}", path.display()))
+        })?;
+        Ok(Self {
+            bytes: plan.total_bytes,
+            files: plan.files.len() as u64,
+        })
+    }
+}
+
+/// Totals of a multi-target operation, shared by every item in it
+struct Batch {
+    multi: MultiProgress,
+    overall: Option<ProgressBar>,
+    footprints: HashMap<String, Footprint>,
+    total: Footprint,
+    done: Footprint,
+    started: Instant,
+    last_line: Option<Instant>,
+}
+
+/// Renders copy progress.
+///
+/// On a terminal each item gets a bar, and a batch started with
+/// [`ProgressDisplay::begin_batch`] adds an overall bar with throughput and
+/// ETA for the whole operation. Otherwise plain, rate-limited lines are
+/// printed so logs stay readable.
+pub struct ProgressDisplay {
+    interactive: bool,
+    batch: RefCell<Option<Batch>>,
+}
+
+impl Default for ProgressDisplay {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ProgressDisplay {
+    pub fn new() -> Self {
+        Self {
+            interactive: io::stdout().is_terminal(),
+            batch: RefCell::new(None),
+        }
+    }
+
+    /// Start tracking a batch whose targets have the given footprints
+    pub fn begin_batch(&self, footprints: HashMap<String, Footprint>) {
+        let total = footprints
+            .values()
+            .fold(Footprint::default(), |sum, item| Footprint {
+                bytes: sum.bytes + item.bytes,
+                files: sum.files + item.files,
+            });
+
+        let multi = MultiProgress::new();
+        let overall = (self.interactive && total.bytes > 0).then(|| {
+            let bar = multi.add(ProgressBar::new(total.bytes));
+            bar.set_style(Self::style(OVERALL_PROGRESS_BAR_TEMPLATE));
+            bar.set_message(format!("0/{} files", total.files));
+            bar
+        });
+
+        *self.batch.borrow_mut() = Some(Batch {
+            multi,
+            overall,
+            footprints,
+            total,
+            done: Footprint::default(),
+            started: Instant::now(),
+            last_line: None,
+        });
+    }
+
+    /// Stop tracking the current batch and remove its bars
+    pub fn end_batch(&self) {
+        if let Some(batch) = self.batch.borrow_mut().take() {
+            if let Some(overall) = batch.overall {
+                overall.finish_and_clear();
+            }
+        }
+    }
+
+    /// Start reporting one copy; `key` is the absolute source path
+    pub fn start_item(&self, key: &str, label: &str) -> ItemProgress<'_> {
+        ItemProgress {
+            display: self,
+            key: key.to_string(),
+            label: label.to_string(),
+            bar: None,
+            total: 0,
+            bytes: 0,
+            files: 0,
+            last_line: None,
+        }
+    }
+
+    fn style(template: &str) -> ProgressStyle {
+        // Use a fallback template if the primary one fails
+        ProgressStyle::default_bar()
+            .template(template)
+            .unwrap_or_else(|_| {
+                ProgressStyle::default_bar()
+                    .template(FALLBACK_PROGRESS_BAR_TEMPLATE)
+                    .unwrap_or_else(|_| ProgressStyle::default_bar())
+            })
+            .progress_chars("#>-")
+    }
+
+    fn update(&self, item: &mut ItemProgress) {
+        let mut batch = self.batch.borrow_mut();
+        match batch.as_mut() {
+            Some(batch) => {
+                let current = Footprint {
+                    bytes: batch.done.bytes + item.bytes,
+                    files: batch.done.files + item.files,
+                };
+                if self.interactive {
+                    if let Some(overall) = &batch.overall {
+                        overall.set_position(current.bytes);
+                        overall
+                            .set_message(format!("{}/{} files", current.files, batch.total.files));
+                    }
+                    let bar = item.bar.get_or_insert_with(|| {
+                        let bar = batch.multi.add(ProgressBar::new(item.total));
+                        bar.set_style(Self::style(ITEM_PROGRESS_BAR_TEMPLATE));
+                        bar.set_message(item.label.clone());
+                        bar
+                    });
+                    bar.set_position(item.bytes);
+                } else if due(batch.last_line) {
+                    batch.last_line = Some(Instant::now());
+                    println!("{}", batch_line(batch, current));
+                }
+            }
+            None if self.interactive => {
+                let bar = item.bar.get_or_insert_with(|| {
+                    let bar = ProgressBar::new(item.total);
+                    bar.set_style(Self::style(PRIMARY_PROGRESS_BAR_TEMPLATE));
+                    bar
+                });
+                bar.set_position(item.bytes);
+                bar.set_message(item.label.clone());
+            }
+            None => {
+                if due(item.last_line) {
+                    item.last_line = Some(Instant::now());
+                    println!(
+                        "{}: {}/{}",
+                        item.label,
+                        HumanBytes(item.bytes),
+                        HumanBytes(item.total)
+                    );
+                }
+            }
+        }
+    }
+
+    fn finish_item(&self, item: &ItemProgress) {
+        let mut batch = self.batch.borrow_mut();
+        let Some(batch) = batch.as_mut() else {
+            return;
+        };
+
+        // The precomputed footprint keeps the totals consistent even if the tree changed
+        let footprint = batch
+            .footprints
+            .get(&item.key)
+            .copied()
+            .unwrap_or(Footprint {
+                bytes: item.bytes,
+                files: item.files,
+            });
+        batch.done.bytes += footprint.bytes;
+        batch.done.files += footprint.files;
+
+        let done = batch.done;
+        if let Some(overall) = &batch.overall {
+            overall.set_position(done.bytes);
+            overall.set_message(format!("{}/{} files", done.files, batch.total.files));
+        } else if !self.interactive {
+            batch.last_line = Some(Instant::now());
+            println!("{}", batch_line(batch, done));
+        }
+    }
+}
+
+/// Progress of a single copy within the display
+pub struct ItemProgress<'a> {
+    display: &'a ProgressDisplay,
+    key: String,
+    label: String,
+    bar: Option<ProgressBar>,
+    total: u64,
+    bytes: u64,
+    files: u64,
+    last_line: Option<Instant>,
+}
+
+impl ItemProgress<'_> {
+    /// Set the number of bytes this item will write
+    pub fn set_total(&mut self, total: u64) {
+        self.total = total;
+    }
+
+    pub fn record(&mut self, event: CopyEvent) {
+        match event {
+            CopyEvent::Bytes(bytes) => self.bytes += bytes,
+            CopyEvent::FileDone => self.files += 1,
+        }
+        let display = self.display;
+        display.update(self);
+    }
+
+    /// Count the item as complete in the batch totals
+    pub fn finish(self) {
+        self.display.finish_item(&self);
+    }
+}
+
+impl Drop for ItemProgress<'_> {
+    fn drop(&mut self) {
+        if let Some(bar) = self.bar.take() {
+            bar.finish_and_clear();
+        }
+        // Commands print between items; bars left on screen would be redrawn over that text
+        if let Some(batch) = self.display.batch.borrow().as_ref() {
+            let _ = batch.multi.clear();
+        }
+    }
+}
+
+fn due(last_line: Option<Instant>) -> bool {
+    last_line.is_none_or(|last| last.elapsed() >= PLAIN_LINE_INTERVAL)
+}
+
+fn batch_line(batch: &Batch, current: Footprint) -> String {
+    let percent = (current.bytes * 100)
+        .checked_div(batch.total.bytes)
+        .unwrap_or(100);
+    let elapsed = batch.started.elapsed().as_secs_f64();
+    let rate = if elapsed > 0.0 {
+        current.bytes as f64 / elapsed
+    } else {
+        0.0
+    };
+    let eta = if rate > 0.0 {
+        let remaining = batch.total.bytes.saturating_sub(current.bytes) as f64 / rate;
+        HumanDuration(Duration::from_secs_f64(remaining)).to_string()
+    } else {
+        "unknown".to_string()
+    };
+
+    format!(
+        "[{percent:>3}%] {}/{}, {}/{} files, {}/s, ETA {eta}",
+        HumanBytes(current.bytes),
+        HumanBytes(batch.total.bytes),
+        current.files,
+        batch.total.files,
+        HumanBytes(rate as u64)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn plain_display() -> ProgressDisplay {
+        ProgressDisplay {
+            interactive: false,
+            batch: RefCell::new(None),
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn footprint_counts_links_only_when_following_them() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("a"), [0; 10]).unwrap();
+        fs::create_dir(dir.path().join("sub")).unwrap();
+        fs::write(dir.path().join("sub/b"), [0; 32]).unwrap();
+        std::os::unix::fs::symlink("a", dir.path().join("link")).unwrap();
+
+        assert_eq!(
+            Footprint::measure(dir.path(), &PathFilters::default(), true).unwrap(),
+            Footprint {
+                bytes: 52,
+                files: 3
+            }
+        );
+        assert_eq!(
+            Footprint::measure(dir.path(), &PathFilters::default(), false).unwrap(),
+            Footprint {
+                bytes: 42,
+                files: 2
+            }
+        );
+    }
+
+    #[test]
+    fn footprint_leaves_out_what_the_filters_exclude() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("keep.txt"), [0; 10]).unwrap();
+        fs::create_dir(dir.path().join("target")).unwrap();
+        fs::write(dir.path().join("target/app"), [0; 32]).unwrap();
+        let filters = PathFilters {
+            exclude: vec!["target/".to_string()],
+            ..PathFilters::default()
+        };
+
+        assert_eq!(
+            Footprint::measure(dir.path(), &filters, false).unwrap(),
+            Footprint {
+                bytes: 10,
+                files: 1
+            }
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn footprint_errors_name_the_entry_that_failed() {
+        let dir = tempfile::tempdir().unwrap();
+        let dangling = dir.path().join("sub/dangling");
+        fs::create_dir(dir.path().join("sub")).unwrap();
+        std::os::unix::fs::symlink("missing", &dangling).unwrap();
+
+        let err = Footprint::measure(dir.path(), &PathFilters::default(), true).unwrap_err();
+        assert!(
+            err.to_string().contains(&dangling.display().to_string()),
+            "{err}"
+        );
+        assert!(Footprint::measure(dir.path(), &PathFilters::default(), false).is_ok());
+    }
+
+    #[test]
+    fn plain_lines_report_the_share_of_the_whole_batch() {
+        let display = plain_display();
+        display.begin_batch(HashMap::from([
+            (
+                "big".to_string(),
+                Footprint {
+                    bytes: 300,
+                    files: 3,
+                },
+            ),
+            (
+                "small".to_string(),
+                Footprint {
+                    bytes: 100,
+                    files: 1,
+                },
+            ),
+        ]));
+
+        let mut item = display.start_item("big", "big");
+        item.set_total(300);
+        item.record(CopyEvent::Bytes(100));
+        // Finished items count with their measured footprint, not what was reported
+        item.finish();
+
+        let batch = display.batch.borrow();
+        let batch = batch.as_ref().unwrap();
+        assert_eq!(
+            batch.total,
+            Footprint {
+                bytes: 400,
+                files: 4
+            }
+        );
+        assert_eq!(
+            batch.done,
+            Footprint {
+                bytes: 300,
+                files: 3
+            }
+        );
+        let line = batch_line(batch, batch.done);
+        assert!(
+            line.starts_with("[ 75%] 300 B/400 B, 3/4 files, "),
+            "{line}"
+        );
+    }
+
+    #[test]
+    fn plain_line_of_an_empty_batch_is_complete() {
+        let display = plain_display();
+        display.begin_batch(HashMap::new());
+
+        let batch = display.batch.borrow();
+        let line = batch_line(batch.as_ref().unwrap(), Footprint::default());
+        assert!(line.starts_with("[100%] 0 B/0 B, 0/0 files, "), "{line}");
+    }
+}
@@ -0,0 +1,327 @@
+use crate::fuga::{SyncChange, SyncChangeKind, SyncOptions};
+use crate::services::checksum::file_digest;
+use crate::services::copy_engine::{with_path, FileJob, LinkJob};
+use crate::services::filter::{Scope, TreeFilter};
+use filetime::FileTime;
+use std::ffi::OsString;
+use std::fs::{self, Metadata};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Modification times closer than this count as equal; FAT stores them in two second steps
+const MTIME_WINDOW: Duration = Duration::from_secs(2);
+
+/// Everything a sync has to change, gathered before the destination is touched
+#[derive(Debug, Default)]
+pub struct SyncPlan {
+    /// Changes to report, in tree order
+    pub changes: Vec<SyncChange>,
+    /// Destination entries to delete, either extraneous or in the way of a source of another type
+    pub remove: Vec<PathBuf>,
+    /// Destination directories, parents before children
+    pub dirs: Vec<PathBuf>,
+    /// Files that are new or changed
+    pub files: Vec<FileJob>,
+    /// Symbolic links that are new or point somewhere else
+    pub links: Vec<LinkJob>,
+    pub total_bytes: u64,
+    pub unchanged: usize,
+}
+
+impl SyncPlan {
+    /// Compare `src` with `dst` and collect what differs.
+    ///
+    /// Like copies, symbolic links in the source are followed with
+    /// `dereference` and mirrored as links otherwise; dangling links have
+    /// nothing to follow and are always mirrored. Inside the destination links
+    /// are treated as entries of their own and replaced.
+    pub fn build(src: &Path, dst: &Path, options: &SyncOptions) -> io::Result<Self> {
+        let filter = TreeFilter::new(&options.filters)?;
+        let mut plan = Self::default();
+        let label = dst.file_name().map(PathBuf::from).unwrap_or_default();
+        // The destination root itself may be reached through a link, e.g. a mount point alias
+        let dst_info = fs::metadata(dst).ok();
+        plan.add(src, dst, dst_info, &label, &filter.scope(src), options)?;
+        Ok(plan)
+    }
+
+    /// Add `src`, using `scope` for its entries when it is a directory
+    fn add(
+        &mut self,
+        src: &Path,
+        dst: &Path,
+        dst_info: Option<Metadata>,
+        label: &Path,
+        scope: &Scope,
+        options: &SyncOptions,
+    ) -> io::Result<()> {
+        let src_info = match options.dereference {
+            true => fs::metadata(src).or_else(|e| match fs::symlink_metadata(src) {
+                Ok(info) if info.is_symlink() => Ok(info),
+                _ => Err(e),
+            }),
+            false => fs::symlink_metadata(src),
+        }
+        .map_err(|e| with_path(e, src))?;
+
+        if src_info.is_symlink() {
+            let target = fs::read_link(src).map_err(|e| with_path(e, src))?;
+            // Checked directly, since the destination root may have been looked up through a link
+            if fs::read_link(dst).is_ok_and(|current| current == target) {
+                self.unchanged += 1;
+                return Ok(());
+            }
+            let kind = match fs::symlink_metadata(dst) {
+                Ok(_) => {
+                    self.remove.push(dst.to_path_buf());
+                    SyncChangeKind::Updated
+                }
+                Err(_) => SyncChangeKind::Added,
+            };
+            self.change(kind, label, false);
+            self.links.push(LinkJob {
+                src: src.to_path_buf(),
+                target,
+                dst: dst.to_path_buf(),
+            });
+            return Ok(());
+        }
+
+        if !src_info.is_dir() {
+            let kind = match &dst_info {
+                None => Some(SyncChangeKind::Added),
+                Some(info) if !info.is_file() => {
+                    self.remove.push(dst.to_path_buf());
+                    Some(SyncChangeKind::Updated)
+                }
+                Some(info) if same_file(src, &src_info, dst, info, options)? => None,
+                Some(_) => Some(SyncChangeKind::Updated),
+            };
+            match kind {
+                Some(kind) => {
+                    self.change(kind, label, false);
+                    self.total_bytes += src_info.len();
+                    self.files.push(FileJob {
+                        src: src.to_path_buf(),
+                        dst: dst.to_path_buf(),
+                    });
+                }
+                None => self.unchanged += 1,
+            }
+            return Ok(());
+        }
+
+        let existing = match &dst_info {
+            Some(info) if info.is_dir() => Some(sorted_entries(dst)?),
+            Some(_) => {
+                self.remove.push(dst.to_path_buf());
+                self.change(SyncChangeKind::Updated, label, true);
+                None
+            }
+            None => {
+                self.change(SyncChangeKind::Added, label, true);
+                None
+            }
+        };
+        self.dirs.push(dst.to_path_buf());
+
+        let mut entries = Vec::new();
+        for name in sorted_entries(src)? {
+            let src_child = src.join(&name);
+            let is_dir = match options.dereference {
+                true => fs::metadata(&src_child),
+                false => fs::symlink_metadata(&src_child),
+            }
+            .is_ok_and(|info| info.is_dir());
+            if scope.allows(&src_child, is_dir) {
+                entries.push((name, src_child, is_dir));
+            }
+        }
+        for (name, src_child, is_dir) in &entries {
+            let dst_child = dst.join(name);
+            // Only a directory that stays in place has children worth comparing
+            let child_info = match existing {
+                Some(_) => fs::symlink_metadata(&dst_child).ok(),
+                None => None,
+            };
+            let child = is_dir.then(|| scope.child(src_child));
+            self.add(
+                src_child,
+                &dst_child,
+                child_info,
+                &label.join(name),
+                child.as_ref().unwrap_or(scope),
+                options,
+            )?;
+        }
+
+        if options.delete {
+            for name in existing.iter().flatten() {
+                if entries.iter().any(|(kept, _, _)| kept == name) {
+                    continue;
+                }
+                let path = dst.join(name);
+                let is_dir = fs::symlink_metadata(&path).is_ok_and(|info| info.is_dir());
+                // Excluded entries are outside the sync and never deleted
+                if scope.allows(&src.join(name), is_dir) {
+                    self.change(SyncChangeKind::Deleted, &label.join(name), is_dir);
+                    self.remove.push(path);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn change(&mut self, kind: SyncChangeKind, label: &Path, is_dir: bool) {
+        let mut path = label.display().to_string();
+        if is_dir {
+            path.push('/');
+        }
+        self.changes.push(SyncChange { kind, path });
+    }
+}
+
+/// Give every copied file its source's modification time, so the next sync sees it as current
+pub fn stamp_files(files: &[FileJob]) -> io::Result<()> {
+    for job in files {
+        let info = fs::metadata(&job.src).map_err(|e| with_path(e, &job.src))?;
+        filetime::set_file_times(
+            &job.dst,
+            FileTime::from_last_access_time(&info),
+            FileTime::from_last_modification_time(&info),
+        )
+        .map_err(|e| with_path(e, &job.dst))?;
+    }
+    Ok(())
+}
+
+fn same_file(
+    src: &Path,
+    src_info: &Metadata,
+    dst: &Path,
+    dst_info: &Metadata,
+    options: &SyncOptions,
+) -> io::Result<bool> {
+    if src_info.len() != dst_info.len() {
+        return Ok(false);
+    }
+    if let Some(algorithm) = options.checksum {
+        return Ok(file_digest(src, algorithm, false)? == file_digest(dst, algorithm, false)?);
+    }
+
+    let (src_time, dst_time) = (src_info.modified()?, dst_info.modified()?);
+    let difference = src_time
+        .duration_since(dst_time)
+        .or_else(|_| dst_time.duration_since(src_time))
+        .unwrap_or_default();
+    Ok(difference < MTIME_WINDOW)
+}
+
+fn sorted_entries(dir: &Path) -> io::Result<Vec<OsString>> {
+    let mut names = fs::read_dir(dir)
+        .map_err(|e| with_path(e, dir))?
+        .map(|entry| entry.map(|entry| entry.file_name()))
+        .collect::<io::Result<Vec<_>>>()
+        .map_err(|e| with_path(e, dir))?;
+    names.sort();
+    Ok(names)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(plan: &SyncPlan) -> Vec<(SyncChangeKind, &str)> {
+        plan.changes
+            .iter()
+            .map(|change| (change.kind, change.path.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn plan_lists_added_updated_and_deleted_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let (src, dst) = (dir.path().join("docs"), dir.path().join("backup/docs"));
+        fs::create_dir_all(src.join("new")).unwrap();
+        fs::create_dir_all(dst.join("old")).unwrap();
+        fs::write(src.join("same.txt"), "same").unwrap();
+        fs::write(src.join("changed.txt"), "longer now").unwrap();
+        fs::write(src.join("new/file.txt"), "new").unwrap();
+        fs::write(dst.join("changed.txt"), "short").unwrap();
+        fs::write(dst.join("extra.txt"), "extra").unwrap();
+        fs::write(dst.join("old/file.txt"), "old").unwrap();
+        fs::copy(src.join("same.txt"), dst.join("same.txt")).unwrap();
+        stamp_files(&[FileJob {
+            src: src.join("same.txt"),
+            dst: dst.join("same.txt"),
+        }])
+        .unwrap();
+
+        let options = SyncOptions {
+            delete: true,
+            ..SyncOptions::default()
+        };
+        let plan = SyncPlan::build(&src, &dst, &options).unwrap();
+
+        assert_eq!(
+            kinds(&plan),
+            [
+                (SyncChangeKind::Updated, "docs/changed.txt"),
+                (SyncChangeKind::Added, "docs/new/"),
+                (SyncChangeKind::Added, "docs/new/file.txt"),
+                (SyncChangeKind::Deleted, "docs/extra.txt"),
+                (SyncChangeKind::Deleted, "docs/old/"),
+            ]
+        );
+        assert_eq!(plan.unchanged, 1);
+        assert_eq!(plan.files.len(), 2);
+
+        let keep = SyncPlan::build(&src, &dst, &SyncOptions::default()).unwrap();
+        assert!(keep.remove.is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn plan_mirrors_dangling_links_and_follows_the_rest_only_when_asked() {
+        use std::os::unix::fs::symlink;
+
+        let dir = tempfile::tempdir().unwrap();
+        let (src, dst) = (dir.path().join("docs"), dir.path().join("backup/docs"));
+        fs::create_dir_all(&src).unwrap();
+        fs::create_dir_all(&dst).unwrap();
+        fs::write(src.join("data.txt"), "data").unwrap();
+        symlink("data.txt", src.join("link")).unwrap();
+        symlink("missing", src.join("dangling")).unwrap();
+        symlink("elsewhere", dst.join("dangling")).unwrap();
+
+        let followed = SyncOptions {
+            dereference: true,
+            ..SyncOptions::default()
+        };
+        let plan = SyncPlan::build(&src, &dst, &followed).unwrap();
+        assert_eq!(
+            kinds(&plan),
+            [
+                (SyncChangeKind::Updated, "docs/dangling"),
+                (SyncChangeKind::Added, "docs/data.txt"),
+                (SyncChangeKind::Added, "docs/link"),
+            ]
+        );
+        assert_eq!(plan.files.len(), 2);
+        assert_eq!(plan.links.len(), 1);
+        assert_eq!(plan.links[0].target, Path::new("missing"));
+        assert_eq!(plan.remove, [dst.join("dangling")]);
+
+        let kept = SyncPlan::build(&src, &dst, &SyncOptions::default()).unwrap();
+        assert_eq!(kept.files.len(), 1);
+        assert_eq!(kept.links.len(), 2);
+
+        // A link that already points the same way is left alone
+        fs::remove_file(dst.join("dangling")).unwrap();
+        symlink("missing", dst.join("dangling")).unwrap();
+        let again = SyncPlan::build(&src, &dst, &followed).unwrap();
+        assert!(again.links.is_empty());
+        assert_eq!(again.unchanged, 1);
+    }
+}
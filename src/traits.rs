@@ -1,7 +1,11 @@
 /// Traits for abstracting external dependencies and enabling dependency injection
-use crate::config::AppConfig;
+use crate::config::{AppConfig, JournalEntry, MarkRecord, UnfinishedCopy};
 use crate::error::FugaResult;
-use crate::fuga::{FileInfo, TargetType};
+use crate::fuga::{
+    ConflictPolicy, FileInfo, PathFilters, SyncOptions, SyncSummary, TargetType, TransferOptions,
+    TransferOutcome,
+};
+use std::collections::BTreeMap;
 
 /// Trait for configuration management operations
 pub trait ConfigRepository {
@@ -17,6 +21,12 @@ pub trait ConfigRepository {
     /// Retrieve the list of marked targets, migrating legacy state if required
     fn get_marked_targets(&self) -> FugaResult<Vec<String>>;
 
+    /// Retrieve the marks with what was recorded about them, migrating legacy state if required
+    fn get_mark_records(&self) -> FugaResult<Vec<MarkRecord>>;
+
+    /// Persist the full list of marks
+    fn set_mark_records(&self, records: &[MarkRecord]) -> FugaResult<()>;
+
     /// Reset the marked targets to an empty list
     fn reset_marks(&self) -> FugaResult<()>;
 
@@ -31,6 +41,27 @@ pub trait ConfigRepository {
 
     /// Delete the preset with the given name, returning whether it existed
     fn delete_preset(&self, name: &str) -> FugaResult<bool>;
+
+    /// Retrieve the operation journal, oldest entry first
+    fn get_journal(&self) -> FugaResult<Vec<JournalEntry>>;
+
+    /// Replace the operation journal
+    fn set_journal(&self, entries: &[JournalEntry]) -> FugaResult<()>;
+
+    /// Append an entry to the operation journal, dropping the oldest beyond the limit
+    fn append_journal(&self, entry: &JournalEntry) -> FugaResult<()>;
+
+    /// Retrieve the copy that was started last and has not finished
+    fn get_unfinished_copy(&self) -> FugaResult<Option<UnfinishedCopy>>;
+
+    /// Record a copy as started, or clear the record once it finished
+    fn set_unfinished_copy(&self, copy: Option<&UnfinishedCopy>) -> FugaResult<()>;
+
+    /// Retrieve the filters stored for marked paths
+    fn get_mark_filters(&self) -> FugaResult<BTreeMap<String, PathFilters>>;
+
+    /// Store the filters of a marked path; empty filters remove the entry
+    fn set_mark_filters(&self, path: &str, filters: &PathFilters) -> FugaResult<()>;
 }
 
 /// Trait for file system operations
@@ -44,14 +75,81 @@ pub trait FileSystemService {
     /// Get the type of the target file or directory
     fn get_file_type(&self, path: &str) -> TargetType;
 
-    /// Copy files or directories
-    fn copy_items(&self, src: &str, dst: &str) -> FugaResult<()>;
+    /// List every entry below a directory, sorted and depth first, without entering linked directories
+    ///
+    /// `depth` limits how many levels are listed, 1 being the directory's own
+    /// entries. Subdirectories that cannot be read are skipped.
+    fn list_tree(&self, dir: &str, depth: Option<usize>) -> FugaResult<Vec<String>>;
+
+    /// Verify that a file or directory can be read
+    fn check_readable(&self, path: &str) -> FugaResult<()>;
+
+    /// Verify that new entries can be created inside the directory
+    fn check_writable_dir(&self, path: &str) -> FugaResult<()>;
+
+    /// Verify that `path` and the directory `dir` are on the same filesystem
+    fn check_same_filesystem(&self, path: &str, dir: &str) -> FugaResult<()>;
+
+    /// Fail with `Cancelled` once the user pressed Ctrl-C during an operation
+    fn check_cancelled(&self) -> FugaResult<()>;
 
-    /// Move files or directories
-    fn move_items(&self, src: &str, dst: &str) -> FugaResult<()>;
+    /// Measure the sources of a batch, each with the filters its copy applies, so
+    /// progress can be shown for all of it; links are followed only with `dereference`
+    fn begin_progress(
+        &self,
+        sources: &[(String, PathFilters)],
+        dereference: bool,
+    ) -> FugaResult<()>;
+
+    /// Clear the progress display of the current batch
+    fn end_progress(&self);
+
+    /// Remove a file, symbolic link, or directory tree without following links
+    fn remove_items(&self, path: &str) -> FugaResult<()>;
+
+    /// Create a directory along with any missing parents
+    fn create_dir(&self, path: &str) -> FugaResult<()>;
+
+    /// Copy files or directories, resolving an existing destination per `options`
+    ///
+    /// The copy is not verified; see `verify_copy`.
+    fn copy_items(
+        &self,
+        src: &str,
+        dst: &str,
+        options: &TransferOptions,
+    ) -> FugaResult<TransferOutcome>;
+
+    /// Hash what a copy from `src` to `dst` wrote with the `verify` algorithm of `options`
+    /// and return the copied files that differ from their source
+    fn verify_copy(
+        &self,
+        src: &str,
+        dst: &str,
+        options: &TransferOptions,
+    ) -> FugaResult<Vec<String>>;
 
-    /// Create symbolic links
-    fn link_items(&self, src: &str, dst: &str) -> FugaResult<()>;
+    /// Move files or directories, resolving an existing destination per `options`
+    fn move_items(
+        &self,
+        src: &str,
+        dst: &str,
+        options: &TransferOptions,
+    ) -> FugaResult<TransferOutcome>;
+
+    /// Whether moving `src` to `dst` has to copy its data rather than rename it
+    fn move_copies(&self, src: &str, dst: &str, options: &TransferOptions) -> FugaResult<bool>;
+
+    /// Create symbolic links, resolving an existing destination per `options`
+    fn link_items(
+        &self,
+        src: &str,
+        dst: &str,
+        options: &TransferOptions,
+    ) -> FugaResult<TransferOutcome>;
+
+    /// Mirror a source into `dst`, copying only new or changed files
+    fn sync_items(&self, src: &str, dst: &str, options: &SyncOptions) -> FugaResult<SyncSummary>;
 }
 
 /// Trait for UI operations
@@ -64,6 +162,9 @@ pub trait UIService {
 
     /// Get icon for target type
     fn get_icon_for_target_type(&self, target_type: TargetType) -> String;
+
+    /// Ask the user how to handle an existing destination
+    fn prompt_conflict(&self, destination: &str) -> FugaResult<ConflictPolicy>;
 }
 
 /// Trait for path operations
@@ -198,6 +198,8 @@ struct DashboardApp<'a> {
     filter_input: String,
     filter_mode: bool,
     marks: Vec<String>,
+    /// Marks whose path no longer exists, drawn in red
+    missing_marks: HashSet<String>,
     status: Option<StatusMessage>,
     confirmation: Option<Confirmation>,
     help_open: bool,
@@ -223,6 +225,7 @@ impl<'a> DashboardApp<'a> {
             filter_input: String::new(),
             filter_mode: false,
             marks: Vec::new(),
+            missing_marks: HashSet::new(),
             status: None,
             confirmation: None,
             help_open: false,
@@ -236,6 +239,15 @@ impl<'a> DashboardApp<'a> {
 
     fn refresh_marks(&mut self) -> FugaResult<()> {
         self.marks = self.config_repo.get_marked_targets()?;
+        self.missing_marks = self
+            .marks
+            .iter()
+            .filter(|path| {
+                // Paths that cannot be checked are not reported as missing
+                matches!(self.fs_service.get_file_info(path), Ok(info) if !info.exists)
+            })
+            .cloned()
+            .collect();
         Ok(())
     }
 
@@ -335,7 +347,9 @@ impl<'a> DashboardApp<'a> {
                 .iter()
                 .enumerate()
                 .map(|(idx, path)| {
-                    let style = if idx % 2 == 0 {
+                    let style = if self.missing_marks.contains(path) {
+                        Style::default().fg(Color::Red)
+                    } else if idx % 2 == 0 {
                         Style::default().fg(Color::White).bg(Color::Rgb(28, 36, 52))
                     } else {
                         Style::default().fg(Color::Black).bg(Color::Blue)
@@ -550,11 +564,9 @@ impl<'a> DashboardApp<'a> {
                     self.filter_input.pop();
                     self.rebuild_visible();
                 }
-                KeyCode::Char(ch) => {
-                    if !key.modifiers.contains(KeyModifiers::CONTROL) {
-                        self.filter_input.push(ch);
-                        self.rebuild_visible();
-                    }
+                KeyCode::Char(ch) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    self.filter_input.push(ch);
+                    self.rebuild_visible();
                 }
                 _ => {}
             }
@@ -759,11 +771,9 @@ impl<'a> DashboardApp<'a> {
     fn remove_mark(&mut self, abs_path: &str) -> FugaResult<()> {
         let mut marks = self.config_repo.get_marked_targets()?;
         marks.retain(|m| m != abs_path);
-        self.config_repo.reset_marks()?;
-        if !marks.is_empty() {
-            dedupe_preserving_order(&mut marks);
-            self.config_repo.set_marked_targets(&marks)?;
-        }
+        dedupe_preserving_order(&mut marks);
+        // Setting the list keeps the records of the remaining marks
+        self.config_repo.set_marked_targets(&marks)?;
         Ok(())
     }
 
@@ -860,11 +870,9 @@ impl<'a> DashboardApp<'a> {
                     popup.filter_input.pop();
                     popup.rebuild_visible();
                 }
-                KeyCode::Char(ch) => {
-                    if !key.modifiers.contains(KeyModifiers::CONTROL) {
-                        popup.filter_input.push(ch);
-                        popup.rebuild_visible();
-                    }
+                KeyCode::Char(ch) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    popup.filter_input.push(ch);
+                    popup.rebuild_visible();
                 }
                 _ => {}
             }
@@ -995,10 +1003,8 @@ impl<'a> DashboardApp<'a> {
             KeyCode::Backspace => {
                 prompt.buffer.pop();
             }
-            KeyCode::Char(ch) => {
-                if !key.modifiers.contains(KeyModifiers::CONTROL) {
-                    prompt.buffer.push(ch);
-                }
+            KeyCode::Char(ch) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                prompt.buffer.push(ch);
             }
             _ => {}
         }
@@ -1178,13 +1184,16 @@ fn dedupe_preserving_order(values: &mut Vec<String>) {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::config::AppConfig;
-    use crate::fuga::{FileInfo, TargetType};
+    use crate::config::{AppConfig, JournalEntry, MarkRecord, UnfinishedCopy};
+    use crate::fuga::{
+        FileInfo, PathFilters, SyncOptions, SyncSummary, TargetType, TransferOptions,
+        TransferOutcome,
+    };
     use crate::traits::{ConfigRepository, FileSystemService};
     use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
     use ratatui::widgets::ListState;
     use std::cell::RefCell;
-    use std::collections::HashSet;
+    use std::collections::{BTreeMap, HashSet};
     use std::path::{Path, PathBuf};
     use tempfile::tempdir;
 
@@ -1232,6 +1241,20 @@ mod tests {
             Ok(self.marks.borrow().clone())
         }
 
+        fn get_mark_records(&self) -> FugaResult<Vec<MarkRecord>> {
+            Ok(self
+                .marks
+                .borrow()
+                .iter()
+                .map(|path| MarkRecord::bare(path))
+                .collect())
+        }
+
+        fn set_mark_records(&self, records: &[MarkRecord]) -> FugaResult<()> {
+            *self.marks.borrow_mut() = records.iter().map(|record| record.path.clone()).collect();
+            Ok(())
+        }
+
         fn reset_marks(&self) -> FugaResult<()> {
             self.marks.borrow_mut().clear();
             Ok(())
@@ -1255,6 +1278,34 @@ mod tests {
         fn delete_preset(&self, name: &str) -> FugaResult<bool> {
             Ok(self.presets.borrow_mut().remove(name).is_some())
         }
+
+        fn get_journal(&self) -> FugaResult<Vec<JournalEntry>> {
+            Ok(Vec::new())
+        }
+
+        fn set_journal(&self, _entries: &[JournalEntry]) -> FugaResult<()> {
+            Ok(())
+        }
+
+        fn append_journal(&self, _entry: &JournalEntry) -> FugaResult<()> {
+            Ok(())
+        }
+
+        fn get_unfinished_copy(&self) -> FugaResult<Option<UnfinishedCopy>> {
+            Ok(None)
+        }
+
+        fn set_unfinished_copy(&self, _copy: Option<&UnfinishedCopy>) -> FugaResult<()> {
+            Ok(())
+        }
+
+        fn get_mark_filters(&self) -> FugaResult<BTreeMap<String, PathFilters>> {
+            Ok(BTreeMap::new())
+        }
+
+        fn set_mark_filters(&self, _path: &str, _filters: &PathFilters) -> FugaResult<()> {
+            Ok(())
+        }
     }
 
     #[derive(Default)]
@@ -1278,6 +1329,9 @@ mod tests {
                 exists,
                 is_file: true,
                 is_dir: false,
+                is_symlink: false,
+                size: 0,
+                modified: None,
                 name,
             })
         }
@@ -1290,17 +1344,99 @@ mod tests {
             TargetType::File
         }
 
-        fn copy_items(&self, _src: &str, _dst: &str) -> FugaResult<()> {
+        fn list_tree(&self, _dir: &str, _depth: Option<usize>) -> FugaResult<Vec<String>> {
+            Ok(Vec::new())
+        }
+
+        fn check_readable(&self, _path: &str) -> FugaResult<()> {
+            panic!("check_readable should not be invoked in tests");
+        }
+
+        fn check_writable_dir(&self, _path: &str) -> FugaResult<()> {
+            panic!("check_writable_dir should not be invoked in tests");
+        }
+
+        fn check_same_filesystem(&self, _path: &str, _dir: &str) -> FugaResult<()> {
+            panic!("check_same_filesystem should not be invoked in tests");
+        }
+
+        fn check_cancelled(&self) -> FugaResult<()> {
+            panic!("check_cancelled should not be invoked in tests");
+        }
+
+        fn begin_progress(
+            &self,
+            _sources: &[(String, PathFilters)],
+            _dereference: bool,
+        ) -> FugaResult<()> {
+            panic!("begin_progress should not be invoked in tests");
+        }
+
+        fn end_progress(&self) {
+            panic!("end_progress should not be invoked in tests");
+        }
+
+        fn remove_items(&self, _path: &str) -> FugaResult<()> {
+            panic!("remove_items should not be invoked in tests");
+        }
+
+        fn create_dir(&self, _path: &str) -> FugaResult<()> {
+            panic!("create_dir should not be invoked in tests");
+        }
+
+        fn copy_items(
+            &self,
+            _src: &str,
+            _dst: &str,
+            _options: &TransferOptions,
+        ) -> FugaResult<TransferOutcome> {
             panic!("copy_items should not be invoked in tests");
         }
 
-        fn move_items(&self, _src: &str, _dst: &str) -> FugaResult<()> {
+        fn verify_copy(
+            &self,
+            _src: &str,
+            _dst: &str,
+            _options: &TransferOptions,
+        ) -> FugaResult<Vec<String>> {
+            panic!("verify_copy should not be invoked in tests");
+        }
+
+        fn move_items(
+            &self,
+            _src: &str,
+            _dst: &str,
+            _options: &TransferOptions,
+        ) -> FugaResult<TransferOutcome> {
             panic!("move_items should not be invoked in tests");
         }
 
-        fn link_items(&self, _src: &str, _dst: &str) -> FugaResult<()> {
+        fn move_copies(
+            &self,
+            _src: &str,
+            _dst: &str,
+            _options: &TransferOptions,
+        ) -> FugaResult<bool> {
+            panic!("move_copies should not be invoked in tests");
+        }
+
+        fn link_items(
+            &self,
+            _src: &str,
+            _dst: &str,
+            _options: &TransferOptions,
+        ) -> FugaResult<TransferOutcome> {
             panic!("link_items should not be invoked in tests");
         }
+
+        fn sync_items(
+            &self,
+            _src: &str,
+            _dst: &str,
+            _options: &SyncOptions,
+        ) -> FugaResult<SyncSummary> {
+            panic!("sync_items should not be invoked in tests");
+        }
     }
 
     fn make_entry(base: &Path, file_name: &str) -> (DirEntryData, String) {
@@ -1335,6 +1471,7 @@ mod tests {
             filter_input: String::new(),
             filter_mode: false,
             marks: config.current_marks(),
+            missing_marks: HashSet::new(),
             status: None,
             confirmation: None,
             help_open: false,
@@ -1418,6 +1555,20 @@ mod tests {
             .is_some());
     }
 
+    #[test]
+    fn refresh_marks_tracks_missing_paths() {
+        let config = StubConfigRepository::with_marks(&["/abs/kept", "/abs/gone"]);
+        let fs = StubFileSystemService::default();
+        fs.register_existing("/abs/kept");
+        let temp_dir = tempdir().unwrap();
+        let (entry, _) = make_entry(temp_dir.path(), "notes.txt");
+
+        let mut app = build_app(&config, &fs, temp_dir.path().to_path_buf(), entry);
+        app.refresh_marks().expect("refresh should succeed");
+        assert_eq!(app.marks, vec!["/abs/kept", "/abs/gone"]);
+        assert_eq!(app.missing_marks, HashSet::from(["/abs/gone".to_string()]));
+    }
+
     #[test]
     fn open_preset_loader_populates_popup() {
         let config = StubConfigRepository::with_marks(&[]);
@@ -1,7 +1,9 @@
-use crate::fuga::TargetType;
+use crate::error::FugaResult;
+use crate::fuga::{ConflictPolicy, TargetType};
 use crate::traits::UIService;
 use crossterm::style::{Color, Stylize};
 use std::env;
+use std::io::{self, BufRead, Write};
 
 pub struct TerminalUIService {
     use_emoji: bool,
@@ -50,12 +52,44 @@ impl UIService for TerminalUIService {
         match (self.use_emoji, target_type) {
             (true, TargetType::File) => "📄".to_string(),
             (true, TargetType::Dir) => "📁".to_string(),
+            (true, TargetType::Symlink) => "🔗".to_string(),
+            (true, TargetType::Other) => "⚙️".to_string(),
             (true, TargetType::None) => "❌".to_string(),
             (false, TargetType::File) => "[FILE]".to_string(),
             (false, TargetType::Dir) => "[DIR]".to_string(),
+            (false, TargetType::Symlink) => "[LINK]".to_string(),
+            (false, TargetType::Other) => "[OTHER]".to_string(),
             (false, TargetType::None) => "[ERR]".to_string(),
         }
     }
+
+    fn prompt_conflict(&self, destination: &str) -> FugaResult<ConflictPolicy> {
+        let stdin = io::stdin();
+        loop {
+            print!(
+                "{} : {} already exists. [o]verwrite, [s]kip, [r]ename, [b]ackup, [a]bort? ",
+                self.get_icon_information(),
+                self.colorize(destination, true)
+            );
+            io::stdout().flush()?;
+
+            let mut answer = String::new();
+            if stdin.lock().read_line(&mut answer)? == 0 {
+                // No interactive input available: behave like `fail`
+                println!();
+                return Ok(ConflictPolicy::Fail);
+            }
+
+            match answer.trim().to_ascii_lowercase().as_str() {
+                "o" | "overwrite" => return Ok(ConflictPolicy::Overwrite),
+                "s" | "skip" => return Ok(ConflictPolicy::Skip),
+                "r" | "rename" => return Ok(ConflictPolicy::Rename),
+                "b" | "backup" => return Ok(ConflictPolicy::Backup),
+                "a" | "abort" => return Ok(ConflictPolicy::Fail),
+                _ => continue,
+            }
+        }
+    }
 }
 
 impl Default for TerminalUIService {
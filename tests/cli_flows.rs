@@ -272,8 +272,1541 @@ fn legacy_single_target_is_migrated_to_targets() {
         .stdout(predicate::str::contains(legacy_path.to_str().unwrap()));
 
     let stored = fs::read_to_string(config_root.join("fuga.toml")).unwrap();
-    assert!(stored.contains("targets"));
+    assert!(stored.contains("[[data.marks]]"));
     assert!(!stored
         .lines()
         .any(|line| line.trim_start().starts_with("target =")));
 }
+
+#[test]
+fn plain_target_list_is_migrated_to_mark_records() {
+    let workspace = TempDir::new().unwrap();
+    let config_dir = TempDir::new().unwrap();
+
+    let old = workspace.child("old.txt");
+    old.write_str("old").unwrap();
+    let new = workspace.child("new.txt");
+    new.write_str("new file").unwrap();
+
+    let config_root = config_dir.path().join("fuga");
+    fs::create_dir_all(&config_root).unwrap();
+    fs::write(
+        config_root.join("fuga.toml"),
+        format!("[data]\ntargets = [\"{}\"]\n", old.path().to_str().unwrap()),
+    )
+    .unwrap();
+
+    let mut add = fuga_command(&config_dir);
+    add.current_dir(workspace.path())
+        .args(["mark", "--add", "new.txt", "--note", "fresh"]);
+    add.assert().success();
+
+    let stored = fs::read_to_string(config_root.join("fuga.toml")).unwrap();
+    assert!(!stored.contains("targets ="));
+    assert_eq!(stored.matches("[[data.marks]]").count(), 2);
+    assert!(stored.contains("kind = \"file\""));
+
+    let mut list = fuga_command(&config_dir);
+    list.args(["mark", "--list", "--long"]);
+    let workspace_dir = workspace.path().to_str().unwrap();
+    list.assert()
+        .success()
+        .stdout(predicate::str::contains("TYPE"))
+        .stdout(predicate::str::contains("8 B"))
+        .stdout(predicate::str::contains(format!("(from {workspace_dir})")))
+        .stdout(predicate::str::contains("# fresh"))
+        .stdout(predicate::str::contains(format!(
+            "{:<23}  {}",
+            "-",
+            old.path().to_str().unwrap()
+        )));
+}
+
+#[test]
+fn copy_on_conflict_rename_picks_free_name() {
+    let workspace = TempDir::new().unwrap();
+    let config_dir = TempDir::new().unwrap();
+
+    let source = workspace.child("report.txt");
+    source.write_str("new").unwrap();
+    mark_paths(&config_dir, &[source.path()]);
+
+    let dest = workspace.child("dest");
+    dest.create_dir_all().unwrap();
+    dest.child("report.txt").write_str("old").unwrap();
+    dest.child("report (1).txt").write_str("older").unwrap();
+
+    let mut copy_cmd = fuga_command(&config_dir);
+    copy_cmd.args([
+        "copy",
+        dest.path().to_str().unwrap(),
+        "--on-conflict",
+        "rename",
+    ]);
+    copy_cmd.assert().success();
+
+    dest.child("report.txt").assert("old");
+    dest.child("report (1).txt").assert("older");
+    dest.child("report (2).txt").assert("new");
+}
+
+#[test]
+fn copy_on_conflict_backup_keeps_numbered_backups() {
+    let workspace = TempDir::new().unwrap();
+    let config_dir = TempDir::new().unwrap();
+
+    let source = workspace.child("notes.txt");
+    source.write_str("v3").unwrap();
+    mark_paths(&config_dir, &[source.path()]);
+
+    let dest = workspace.child("dest");
+    dest.create_dir_all().unwrap();
+    dest.child("notes.txt").write_str("v2").unwrap();
+    dest.child("notes.txt~").write_str("v1").unwrap();
+
+    let mut copy_cmd = fuga_command(&config_dir);
+    copy_cmd.args([
+        "copy",
+        dest.path().to_str().unwrap(),
+        "--on-conflict",
+        "backup",
+    ]);
+    copy_cmd
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("backed up to"));
+
+    dest.child("notes.txt").assert("v3");
+    dest.child("notes.txt~").assert("v1");
+    dest.child("notes.txt.~1~").assert("v2");
+}
+
+#[test]
+fn copy_on_conflict_skip_and_fail_leave_destination_untouched() {
+    let workspace = TempDir::new().unwrap();
+    let config_dir = TempDir::new().unwrap();
+
+    let source = workspace.child("data.csv");
+    source.write_str("fresh").unwrap();
+    mark_paths(&config_dir, &[source.path()]);
+
+    let dest = workspace.child("dest");
+    dest.create_dir_all().unwrap();
+    dest.child("data.csv").write_str("kept").unwrap();
+
+    let mut skip_cmd = fuga_command(&config_dir);
+    skip_cmd.args([
+        "copy",
+        dest.path().to_str().unwrap(),
+        "--on-conflict",
+        "skip",
+    ]);
+    skip_cmd
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Skipped"));
+    dest.child("data.csv").assert("kept");
+
+    let mut fail_cmd = fuga_command(&config_dir);
+    fail_cmd.args([
+        "copy",
+        dest.path().to_str().unwrap(),
+        "--on-conflict",
+        "fail",
+    ]);
+    fail_cmd
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Destination already exists"));
+    dest.child("data.csv").assert("kept");
+}
+
+#[test]
+fn move_skipped_targets_stay_marked() {
+    let workspace = TempDir::new().unwrap();
+    let config_dir = TempDir::new().unwrap();
+
+    let moved = workspace.child("moved.txt");
+    let blocked = workspace.child("blocked.txt");
+    moved.write_str("moved").unwrap();
+    blocked.write_str("blocked").unwrap();
+    mark_paths(&config_dir, &[moved.path(), blocked.path()]);
+
+    let dest = workspace.child("dest");
+    dest.create_dir_all().unwrap();
+    dest.child("blocked.txt").write_str("existing").unwrap();
+
+    let mut move_cmd = fuga_command(&config_dir);
+    move_cmd.args([
+        "move",
+        dest.path().to_str().unwrap(),
+        "--on-conflict",
+        "skip",
+    ]);
+    move_cmd.assert().success();
+
+    dest.child("moved.txt").assert("moved");
+    blocked.assert("blocked");
+
+    let mut list_cmd = fuga_command(&config_dir);
+    list_cmd.args(["mark", "--list"]);
+    list_cmd
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(blocked.path().to_str().unwrap()))
+        .stdout(predicate::str::contains(moved.path().to_str().unwrap()).not());
+}
+
+#[test]
+fn move_keep_going_attempts_every_target_and_keeps_failures_marked() {
+    let workspace = TempDir::new().unwrap();
+    let config_dir = TempDir::new().unwrap();
+
+    let first = workspace.child("first.txt");
+    let blocked = workspace.child("blocked.txt");
+    let last = workspace.child("last.txt");
+    first.write_str("first").unwrap();
+    blocked.write_str("blocked").unwrap();
+    last.write_str("last").unwrap();
+    mark_paths(&config_dir, &[first.path(), blocked.path(), last.path()]);
+
+    let dest = workspace.child("dest");
+    dest.create_dir_all().unwrap();
+    dest.child("blocked.txt").write_str("existing").unwrap();
+
+    let mut move_cmd = fuga_command(&config_dir);
+    move_cmd.args([
+        "move",
+        dest.path().to_str().unwrap(),
+        "--on-conflict",
+        "ask",
+        "--keep-going",
+    ]);
+    move_cmd
+        .write_stdin("a\n")
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains(
+            "Move summary: 2 succeeded, 0 skipped, 1 failed.",
+        ))
+        .stderr(predicate::str::contains("1 of 3 target(s) failed"));
+
+    dest.child("first.txt").assert("first");
+    dest.child("last.txt").assert("last");
+    dest.child("blocked.txt").assert("existing");
+    blocked.assert("blocked");
+
+    let mut list_cmd = fuga_command(&config_dir);
+    list_cmd.args(["mark", "--list"]);
+    list_cmd
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(blocked.path().to_str().unwrap()))
+        .stdout(predicate::str::contains(first.path().to_str().unwrap()).not())
+        .stdout(predicate::str::contains(last.path().to_str().unwrap()).not());
+}
+
+#[test]
+fn move_onto_non_empty_directory_merges_into_it() {
+    let workspace = TempDir::new().unwrap();
+    let config_dir = TempDir::new().unwrap();
+
+    let source = workspace.child("d1");
+    source.child("new.txt").write_str("new").unwrap();
+    source.child("sub/deep.txt").write_str("deep").unwrap();
+    mark_paths(&config_dir, &[source.path()]);
+
+    let dest = workspace.child("out");
+    dest.child("d1/keep").write_str("keep").unwrap();
+    dest.child("d1/sub/other.txt").write_str("other").unwrap();
+
+    let mut dry_run = fuga_command(&config_dir);
+    dry_run.args(["move", dest.path().to_str().unwrap(), "--dry-run"]);
+    dry_run.assert().success().stdout(predicate::str::contains(
+        "destination directory exists, would merge into it",
+    ));
+
+    let mut move_cmd = fuga_command(&config_dir);
+    move_cmd.args(["move", dest.path().to_str().unwrap()]);
+    move_cmd.assert().success().stdout(predicate::str::contains(
+        "merged into the existing directory, then removed the source",
+    ));
+
+    source.assert(predicate::path::missing());
+    dest.child("d1/keep").assert("keep");
+    dest.child("d1/new.txt").assert("new");
+    dest.child("d1/sub/deep.txt").assert("deep");
+    dest.child("d1/sub/other.txt").assert("other");
+
+    // Only what moved in goes back; the directory that was there before stays
+    let mut undo_cmd = fuga_command(&config_dir);
+    undo_cmd.arg("undo");
+    undo_cmd.assert().success();
+    source.child("new.txt").assert("new");
+    source.child("sub/deep.txt").assert("deep");
+    dest.child("d1/keep").assert("keep");
+    dest.child("d1/sub/other.txt").assert("other");
+    dest.child("d1/new.txt").assert(predicate::path::missing());
+    dest.child("d1/sub/deep.txt")
+        .assert(predicate::path::missing());
+}
+
+#[test]
+fn preflight_rejects_replacing_a_directory_with_a_file() {
+    let workspace = TempDir::new().unwrap();
+    let config_dir = TempDir::new().unwrap();
+
+    let source = workspace.child("report");
+    source.write_str("report").unwrap();
+    mark_paths(&config_dir, &[source.path()]);
+
+    let dest = workspace.child("out");
+    dest.child("report/keep").write_str("keep").unwrap();
+
+    let mut move_cmd = fuga_command(&config_dir);
+    move_cmd.args(["move", dest.path().to_str().unwrap()]);
+    move_cmd.assert().failure().stderr(predicate::str::contains(
+        "Cannot overwrite a directory with a file",
+    ));
+
+    source.assert("report");
+    dest.child("report/keep").assert("keep");
+}
+
+#[test]
+fn copy_keep_going_records_preflight_problems_and_copies_the_rest() {
+    let workspace = TempDir::new().unwrap();
+    let config_dir = TempDir::new().unwrap();
+
+    let gone = workspace.child("gone.txt");
+    let kept = workspace.child("kept");
+    gone.write_str("gone").unwrap();
+    kept.child("inner.txt").write_str("inner").unwrap();
+    mark_paths(&config_dir, &[gone.path(), kept.path()]);
+    fs::remove_file(gone.path()).unwrap();
+
+    let dest = workspace.child("dest");
+    dest.create_dir_all().unwrap();
+
+    let mut copy_cmd = fuga_command(&config_dir);
+    copy_cmd.args(["copy", dest.path().to_str().unwrap(), "--keep-going"]);
+    copy_cmd
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains(
+            "Copy summary: 1 succeeded, 0 skipped, 1 failed.",
+        ))
+        .stderr(predicate::str::contains("1 of 2 target(s) failed"));
+
+    dest.child("kept/inner.txt").assert("inner");
+    dest.child("gone.txt").assert(predicate::path::missing());
+}
+
+#[test]
+fn dry_run_reports_plan_without_touching_disk() {
+    let workspace = TempDir::new().unwrap();
+    let config_dir = TempDir::new().unwrap();
+
+    let fresh = workspace.child("fresh.txt");
+    let taken = workspace.child("taken.txt");
+    fresh.write_str("fresh").unwrap();
+    taken.write_str("taken").unwrap();
+    mark_paths(&config_dir, &[fresh.path(), taken.path()]);
+
+    let dest = workspace.child("dest");
+    dest.create_dir_all().unwrap();
+    dest.child("taken.txt").write_str("existing").unwrap();
+
+    let mut move_cmd = fuga_command(&config_dir);
+    move_cmd.args(["move", dest.path().to_str().unwrap(), "--dry-run"]);
+    move_cmd
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Dry run"))
+        .stdout(predicate::str::contains("destination is free"))
+        .stdout(predicate::str::contains(
+            "destination exists, would overwrite",
+        ));
+
+    fresh.assert("fresh");
+    taken.assert("taken");
+    dest.child("fresh.txt").assert(predicate::path::missing());
+    dest.child("taken.txt").assert("existing");
+
+    let mut list_cmd = fuga_command(&config_dir);
+    list_cmd.args(["mark", "--list"]);
+    list_cmd
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(fresh.path().to_str().unwrap()));
+}
+
+#[test]
+fn move_preflight_reports_all_problems_before_touching_files() {
+    let workspace = TempDir::new().unwrap();
+    let config_dir = TempDir::new().unwrap();
+
+    let first = workspace.child("first.txt");
+    let second = workspace.child("second.txt");
+    let third = workspace.child("third.txt");
+    first.write_str("1").unwrap();
+    second.write_str("2").unwrap();
+    third.write_str("3").unwrap();
+    mark_paths(&config_dir, &[first.path(), second.path(), third.path()]);
+    fs::remove_file(third.path()).unwrap();
+
+    let dest = workspace.child("dest");
+    dest.create_dir_all().unwrap();
+    dest.child("second.txt").write_str("existing").unwrap();
+
+    let mut move_cmd = fuga_command(&config_dir);
+    move_cmd.args([
+        "move",
+        dest.path().to_str().unwrap(),
+        "--on-conflict",
+        "fail",
+    ]);
+    move_cmd
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("2 problem(s)"))
+        .stderr(predicate::str::contains(third.path().to_str().unwrap()))
+        .stderr(predicate::str::contains("Destination already exists"));
+
+    first.assert("1");
+    second.assert("2");
+    dest.child("first.txt").assert(predicate::path::missing());
+}
+
+#[test]
+fn copy_directory_into_itself_is_rejected() {
+    let workspace = TempDir::new().unwrap();
+    let config_dir = TempDir::new().unwrap();
+
+    let project = workspace.child("project");
+    project.child("src").create_dir_all().unwrap();
+    project
+        .child("src/main.rs")
+        .write_str("fn main() {}")
+        .unwrap();
+    mark_paths(&config_dir, &[project.path()]);
+
+    let mut copy_cmd = fuga_command(&config_dir);
+    copy_cmd.args(["copy", project.child("src").path().to_str().unwrap()]);
+    copy_cmd
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("into itself"));
+
+    project
+        .child("src/project")
+        .assert(predicate::path::missing());
+}
+
+#[test]
+fn atomic_move_rolls_back_completed_targets() {
+    let workspace = TempDir::new().unwrap();
+    let config_dir = TempDir::new().unwrap();
+
+    let first = workspace.child("first.txt");
+    let second = workspace.child("second.txt");
+    first.write_str("1").unwrap();
+    second.write_str("2").unwrap();
+    mark_paths(&config_dir, &[first.path(), second.path()]);
+
+    let dest = workspace.child("dest");
+    dest.create_dir_all().unwrap();
+    dest.child("second.txt").write_str("existing").unwrap();
+
+    // Declining the prompt makes the second target fail after the first moved
+    let mut move_cmd = fuga_command(&config_dir);
+    move_cmd.args([
+        "move",
+        dest.path().to_str().unwrap(),
+        "--atomic",
+        "--on-conflict",
+        "ask",
+    ]);
+    move_cmd.write_stdin("a\n");
+    move_cmd
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("rolling back 1 completed step(s)"))
+        .stderr(predicate::str::contains("Destination already exists"));
+
+    first.assert("1");
+    second.assert("2");
+    dest.child("first.txt").assert(predicate::path::missing());
+    dest.child("second.txt").assert("existing");
+
+    let mut list_cmd = fuga_command(&config_dir);
+    list_cmd.args(["mark", "--list"]);
+    list_cmd
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(first.path().to_str().unwrap()));
+}
+
+#[test]
+fn atomic_copy_removes_completed_copies() {
+    let workspace = TempDir::new().unwrap();
+    let config_dir = TempDir::new().unwrap();
+
+    let first = workspace.child("first.txt");
+    let second = workspace.child("second.txt");
+    first.write_str("1").unwrap();
+    second.write_str("2").unwrap();
+    mark_paths(&config_dir, &[first.path(), second.path()]);
+
+    let dest = workspace.child("dest");
+    dest.create_dir_all().unwrap();
+    dest.child("second.txt").write_str("existing").unwrap();
+
+    let mut copy_cmd = fuga_command(&config_dir);
+    copy_cmd.args([
+        "copy",
+        dest.path().to_str().unwrap(),
+        "--atomic",
+        "--on-conflict",
+        "ask",
+    ]);
+    copy_cmd.write_stdin("");
+    copy_cmd.assert().failure();
+
+    dest.child("first.txt").assert(predicate::path::missing());
+    dest.child("second.txt").assert("existing");
+}
+
+#[test]
+fn atomic_refuses_to_overwrite_what_it_cannot_restore() {
+    let workspace = TempDir::new().unwrap();
+    let config_dir = TempDir::new().unwrap();
+
+    let first = workspace.child("first.txt");
+    let second = workspace.child("second.txt");
+    first.write_str("1").unwrap();
+    second.write_str("2").unwrap();
+    mark_paths(&config_dir, &[first.path(), second.path()]);
+
+    let dest = workspace.child("dest");
+    dest.child("keep")
+        .child("old.txt")
+        .write_str("old")
+        .unwrap();
+    dest.child("second.txt").write_str("existing").unwrap();
+
+    let mut copy_cmd = fuga_command(&config_dir);
+    copy_cmd.args(["copy", dest.path().to_str().unwrap(), "--atomic"]);
+    copy_cmd
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--atomic cannot restore"));
+    dest.child("first.txt").assert(predicate::path::missing());
+    dest.child("second.txt").assert("existing");
+
+    dest.child("keep").child("old.txt").assert("old");
+
+    // Answering overwrite at the prompt is refused the same way
+    let mut ask_cmd = fuga_command(&config_dir);
+    ask_cmd.args([
+        "copy",
+        dest.path().to_str().unwrap(),
+        "--atomic",
+        "--on-conflict",
+        "ask",
+    ]);
+    ask_cmd.write_stdin("o\n");
+    ask_cmd
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--atomic cannot restore"));
+    dest.child("first.txt").assert(predicate::path::missing());
+    dest.child("second.txt").assert("existing");
+}
+
+#[test]
+fn undo_moves_items_back_to_their_origin() {
+    let workspace = TempDir::new().unwrap();
+    let config_dir = TempDir::new().unwrap();
+
+    let file = workspace.child("doc.txt");
+    let dir = workspace.child("assets");
+    file.write_str("doc").unwrap();
+    dir.child("logo.svg").write_str("<svg/>").unwrap();
+    mark_paths(&config_dir, &[file.path(), dir.path()]);
+
+    let dest = workspace.child("dest");
+    dest.create_dir_all().unwrap();
+
+    let mut move_cmd = fuga_command(&config_dir);
+    move_cmd.args(["move", dest.path().to_str().unwrap()]);
+    move_cmd.assert().success();
+    assert!(!file.path().exists());
+
+    let mut undo_cmd = fuga_command(&config_dir);
+    undo_cmd.arg("undo");
+    undo_cmd
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Last move undone."));
+
+    file.assert("doc");
+    dir.child("logo.svg").assert("<svg/>");
+    dest.child("doc.txt").assert(predicate::path::missing());
+    dest.child("assets").assert(predicate::path::missing());
+
+    let mut again = fuga_command(&config_dir);
+    again.arg("undo");
+    again
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Nothing to undo."));
+}
+
+#[test]
+fn undo_copy_removes_copy_and_restores_backup() {
+    let workspace = TempDir::new().unwrap();
+    let config_dir = TempDir::new().unwrap();
+
+    let source = workspace.child("config.yml");
+    source.write_str("new").unwrap();
+    mark_paths(&config_dir, &[source.path()]);
+
+    let dest = workspace.child("dest");
+    dest.create_dir_all().unwrap();
+    dest.child("config.yml").write_str("old").unwrap();
+
+    let mut copy_cmd = fuga_command(&config_dir);
+    copy_cmd.args([
+        "copy",
+        dest.path().to_str().unwrap(),
+        "--on-conflict",
+        "backup",
+    ]);
+    copy_cmd.assert().success();
+    dest.child("config.yml").assert("new");
+
+    let mut undo_cmd = fuga_command(&config_dir);
+    undo_cmd.arg("undo");
+    undo_cmd.assert().success();
+
+    dest.child("config.yml").assert("old");
+    dest.child("config.yml~").assert(predicate::path::missing());
+    source.assert("new");
+}
+
+#[test]
+fn undo_after_merge_keeps_what_was_already_there() {
+    let workspace = TempDir::new().unwrap();
+    let config_dir = TempDir::new().unwrap();
+
+    let source = workspace.child("d1");
+    source.child("new.txt").write_str("new").unwrap();
+    source.child("sub/deep.txt").write_str("deep").unwrap();
+    mark_paths(&config_dir, &[source.path()]);
+
+    let dest = workspace.child("out");
+    dest.child("d1/keep").write_str("keep").unwrap();
+
+    let mut copy_cmd = fuga_command(&config_dir);
+    copy_cmd.args(["copy", dest.path().to_str().unwrap()]);
+    copy_cmd.assert().success();
+    dest.child("d1/new.txt").assert("new");
+
+    // What a merge created is journaled on its own, not in the config read by every command
+    let config_root = config_dir.path().join("fuga");
+    let created = dest.child("d1/new.txt").path().display().to_string();
+    let journal = fs::read_to_string(config_root.join("journal.toml")).unwrap();
+    assert!(journal.contains(&created), "{journal}");
+    let config = fs::read_to_string(config_root.join("fuga.toml")).unwrap();
+    assert!(!config.contains(&created), "{config}");
+
+    let mut undo_cmd = fuga_command(&config_dir);
+    undo_cmd.arg("undo");
+    undo_cmd
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Last copy undone."));
+
+    dest.child("d1/keep").assert("keep");
+    dest.child("d1/new.txt").assert(predicate::path::missing());
+    dest.child("d1/sub").assert(predicate::path::missing());
+
+    // Overwritten contents are gone for good, so undo refuses instead of deleting the rest
+    source.child("keep").write_str("replacement").unwrap();
+    let mut overwrite_cmd = fuga_command(&config_dir);
+    overwrite_cmd.args(["copy", dest.path().to_str().unwrap()]);
+    overwrite_cmd.assert().success();
+
+    let mut refused = fuga_command(&config_dir);
+    refused.arg("undo");
+    refused
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("replaced what was there before"));
+    dest.child("d1/keep").assert("replacement");
+    dest.child("d1/new.txt").assert("new");
+}
+
+#[test]
+fn history_lists_successful_and_failed_operations() {
+    let workspace = TempDir::new().unwrap();
+    let config_dir = TempDir::new().unwrap();
+
+    let source = workspace.child("notes.txt");
+    source.write_str("notes").unwrap();
+    mark_paths(&config_dir, &[source.path()]);
+
+    let dest = workspace.child("dest");
+    dest.create_dir_all().unwrap();
+
+    let mut copy_cmd = fuga_command(&config_dir);
+    copy_cmd.args(["copy", dest.path().to_str().unwrap()]);
+    copy_cmd.assert().success();
+
+    let mut failing_cmd = fuga_command(&config_dir);
+    failing_cmd.args([
+        "copy",
+        dest.path().to_str().unwrap(),
+        "--on-conflict",
+        "ask",
+    ]);
+    failing_cmd.write_stdin("a\n").assert().failure();
+
+    let mut history_cmd = fuga_command(&config_dir);
+    history_cmd.args(["history", "--json"]);
+    let output = history_cmd.assert().success().get_output().stdout.clone();
+    let entries: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    let entries = entries.as_array().unwrap();
+
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0]["status"], "failed");
+    assert!(entries[0]["error"]
+        .as_str()
+        .unwrap()
+        .contains("already exists"));
+    assert_eq!(entries[1]["command"], "copy");
+    assert_eq!(entries[1]["status"], "success");
+    assert_eq!(entries[1]["targets"], 1);
+    let step = &entries[1]["steps"][0];
+    assert!(step["source"].as_str().unwrap().ends_with("notes.txt"));
+    assert!(step["destination"]
+        .as_str()
+        .unwrap()
+        .ends_with("dest/notes.txt"));
+
+    let mut limited_cmd = fuga_command(&config_dir);
+    limited_cmd.args(["history", "--since", "2099-01-01"]);
+    limited_cmd
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No operations recorded."));
+}
+
+#[cfg(unix)]
+#[test]
+fn copy_preserve_keeps_mode_and_timestamps_inside_directories() {
+    let workspace = TempDir::new().unwrap();
+    let config_dir = TempDir::new().unwrap();
+
+    let source = workspace.child("project");
+    source.create_dir_all().unwrap();
+    let script = source.child("run.sh");
+    script.write_str("#!/bin/sh\n").unwrap();
+    fs::set_permissions(script.path(), fs::Permissions::from_mode(0o750)).unwrap();
+    let old = filetime::FileTime::from_unix_time(1_600_000_000, 0);
+    filetime::set_file_mtime(script.path(), old).unwrap();
+    filetime::set_file_mtime(source.path(), old).unwrap();
+    mark_paths(&config_dir, &[source.path()]);
+
+    let dest = workspace.child("dest");
+    dest.create_dir_all().unwrap();
+
+    let mut copy_cmd = fuga_command(&config_dir);
+    copy_cmd.args([
+        "copy",
+        dest.path().to_str().unwrap(),
+        "--preserve",
+        "mode,timestamps",
+    ]);
+    copy_cmd.assert().success();
+
+    let copied = fs::metadata(dest.child("project/run.sh").path()).unwrap();
+    assert_eq!(copied.permissions().mode() & 0o777, 0o750);
+    assert_eq!(
+        filetime::FileTime::from_last_modification_time(&copied),
+        old
+    );
+    let copied_dir = fs::metadata(dest.child("project").path()).unwrap();
+    assert_eq!(
+        filetime::FileTime::from_last_modification_time(&copied_dir),
+        old
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn link_hard_recreates_directories_and_shares_file_data() {
+    use std::os::unix::fs::MetadataExt;
+
+    let workspace = TempDir::new().unwrap();
+    let config_dir = TempDir::new().unwrap();
+
+    let source = workspace.child("assets");
+    source.child("img").create_dir_all().unwrap();
+    source.child("img/logo.png").write_str("png").unwrap();
+    let single = workspace.child("notes.txt");
+    single.write_str("notes").unwrap();
+    mark_paths(&config_dir, &[source.path(), single.path()]);
+
+    let dest = workspace.child("dest");
+    dest.create_dir_all().unwrap();
+
+    let mut link_cmd = fuga_command(&config_dir);
+    link_cmd.args(["link", "--hard", dest.path().to_str().unwrap()]);
+    link_cmd
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("hard-linked."));
+
+    let linked_dir = fs::symlink_metadata(dest.child("assets/img").path()).unwrap();
+    assert!(linked_dir.is_dir());
+    let ino = |path: &Path| fs::metadata(path).unwrap().ino();
+    assert_eq!(
+        ino(dest.child("assets/img/logo.png").path()),
+        ino(source.child("img/logo.png").path())
+    );
+    assert_eq!(ino(dest.child("notes.txt").path()), ino(single.path()));
+}
+
+#[cfg(unix)]
+#[test]
+fn link_relative_survives_moving_the_tree() {
+    let workspace = TempDir::new().unwrap();
+    let config_dir = TempDir::new().unwrap();
+
+    let repo = workspace.child("repo");
+    let target = repo.child("shared/config.toml");
+    target.write_str("key = 1").unwrap();
+    repo.child("app").create_dir_all().unwrap();
+    mark_paths(&config_dir, &[target.path()]);
+
+    let mut link_cmd = fuga_command(&config_dir);
+    link_cmd.args([
+        "link",
+        "--relative",
+        repo.child("app").path().to_str().unwrap(),
+    ]);
+    link_cmd.assert().success();
+
+    assert_eq!(
+        read_link(repo.child("app/config.toml").path()).unwrap(),
+        Path::new("../shared/config.toml")
+    );
+
+    let moved = workspace.child("moved");
+    fs::rename(repo.path(), moved.path()).unwrap();
+    moved.child("app/config.toml").assert("key = 1");
+}
+
+#[cfg(unix)]
+#[test]
+fn move_on_same_filesystem_renames_in_place() {
+    use std::os::unix::fs::MetadataExt;
+
+    let workspace = TempDir::new().unwrap();
+    let config_dir = TempDir::new().unwrap();
+
+    let source = workspace.child("dataset");
+    source.child("part-0").write_str("rows").unwrap();
+    let inode = fs::metadata(source.path()).unwrap().ino();
+    mark_paths(&config_dir, &[source.path()]);
+
+    let dest = workspace.child("archive");
+    dest.create_dir_all().unwrap();
+
+    let mut move_cmd = fuga_command(&config_dir);
+    move_cmd.args(["move", dest.path().to_str().unwrap()]);
+    move_cmd
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("moved (renamed in place)."));
+
+    source.assert(predicate::path::missing());
+    assert_eq!(
+        fs::metadata(dest.child("dataset").path()).unwrap().ino(),
+        inode
+    );
+}
+
+#[test]
+fn copy_directory_over_existing_directory_merges_contents() {
+    let workspace = TempDir::new().unwrap();
+    let config_dir = TempDir::new().unwrap();
+
+    let source = workspace.child("site");
+    source.child("css/main.css").write_str("body {}").unwrap();
+    source.child("index.html").write_str("new").unwrap();
+    mark_paths(&config_dir, &[source.path()]);
+
+    let dest = workspace.child("dest");
+    dest.child("site/index.html").write_str("old").unwrap();
+    dest.child("site/extra.txt").write_str("kept").unwrap();
+
+    let mut copy_cmd = fuga_command(&config_dir);
+    copy_cmd.args(["copy", "--reflink=never", dest.path().to_str().unwrap()]);
+    copy_cmd.assert().success();
+
+    dest.child("site/index.html").assert("new");
+    dest.child("site/css/main.css").assert("body {}");
+    dest.child("site/extra.txt").assert("kept");
+    dest.child("site/site").assert(predicate::path::missing());
+}
+
+#[test]
+fn copy_with_jobs_copies_every_file_of_a_directory() {
+    let workspace = TempDir::new().unwrap();
+    let config_dir = TempDir::new().unwrap();
+
+    let source = workspace.child("node_modules");
+    for package in 0..10 {
+        for file in 0..5 {
+            source
+                .child(format!("pkg-{package}/file-{file}.js"))
+                .write_str(&format!("module {package}/{file}"))
+                .unwrap();
+        }
+    }
+    mark_paths(&config_dir, &[source.path()]);
+
+    let dest = workspace.child("dest");
+    dest.create_dir_all().unwrap();
+
+    let mut copy_cmd = fuga_command(&config_dir);
+    copy_cmd.args(["copy", "--jobs", "4", dest.path().to_str().unwrap()]);
+    copy_cmd.assert().success();
+
+    for package in 0..10 {
+        for file in 0..5 {
+            dest.child(format!("node_modules/pkg-{package}/file-{file}.js"))
+                .assert(format!("module {package}/{file}").as_str());
+        }
+    }
+}
+
+#[test]
+fn copy_verify_checks_every_file_with_either_hash() {
+    let workspace = TempDir::new().unwrap();
+    let config_dir = TempDir::new().unwrap();
+
+    let source = workspace.child("photos");
+    source.child("a.jpg").write_str("first").unwrap();
+    source.child("nested/b.jpg").write_str("second").unwrap();
+    mark_paths(&config_dir, &[source.path()]);
+
+    for (hash, name) in [("--verify", "fast"), ("--verify=sha256", "sha256")] {
+        let dest = workspace.child(name);
+        let mut copy_cmd = fuga_command(&config_dir);
+        copy_cmd.args(["copy", hash, dest.path().to_str().unwrap()]);
+        copy_cmd.assert().success();
+
+        dest.child("a.jpg").assert("first");
+        dest.child("nested/b.jpg").assert("second");
+    }
+
+    let mut bad_hash = fuga_command(&config_dir);
+    bad_hash.args(["copy", "--verify=md5", "elsewhere"]);
+    bad_hash.assert().failure();
+}
+
+#[cfg(unix)]
+#[test]
+fn copy_suggests_and_performs_resume_after_an_unfinished_run() {
+    let workspace = TempDir::new().unwrap();
+    let config_dir = TempDir::new().unwrap();
+
+    let source = workspace.child("album");
+    source.child("01.flac").write_str("first track").unwrap();
+    source.child("02.flac").write_str("second track").unwrap();
+    // Copying the dangling link fails, leaving the run unfinished
+    std::os::unix::fs::symlink(workspace.path().join("gone"), source.path().join("03.flac"))
+        .unwrap();
+    mark_paths(&config_dir, &[source.path()]);
+
+    let dest = workspace.child("backup");
+    dest.create_dir_all().unwrap();
+    let mut failing = fuga_command(&config_dir);
+    failing.args(["copy", dest.path().to_str().unwrap()]);
+    failing.assert().failure();
+
+    fs::remove_file(source.path().join("03.flac")).unwrap();
+    source.child("03.flac").write_str("third track").unwrap();
+    // Pretend the first run stopped halfway through a file
+    dest.child("album/02.flac").write_str("second").unwrap();
+
+    let mut plain = fuga_command(&config_dir);
+    plain.args(["copy", "--dry-run", dest.path().to_str().unwrap()]);
+    plain
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Add --resume to continue it"));
+
+    let mut resume = fuga_command(&config_dir);
+    resume.args(["copy", "--resume", dest.path().to_str().unwrap()]);
+    resume.assert().success();
+
+    dest.child("album/01.flac").assert("first track");
+    dest.child("album/02.flac").assert("second track");
+    dest.child("album/03.flac").assert("third track");
+
+    let mut after = fuga_command(&config_dir);
+    after.args(["copy", "--dry-run", dest.path().to_str().unwrap()]);
+    after
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("--resume").not());
+}
+
+#[test]
+fn sync_copies_only_changes_and_deletes_extraneous_files() {
+    let workspace = TempDir::new().unwrap();
+    let config_dir = TempDir::new().unwrap();
+
+    let source = workspace.child("project");
+    source.child("keep.txt").write_str("keep").unwrap();
+    source.child("edit.txt").write_str("before").unwrap();
+    source.child("drop.txt").write_str("drop").unwrap();
+    mark_paths(&config_dir, &[source.path()]);
+
+    let backup = workspace.child("backup");
+    backup.create_dir_all().unwrap();
+    let sync = |extra: &[&str]| {
+        let mut cmd = fuga_command(&config_dir);
+        cmd.arg("sync").args(extra).arg(backup.path());
+        cmd.assert().success()
+    };
+
+    sync(&[]).stdout(predicate::str::contains(
+        "4 added, 0 updated, 0 deleted, 0 unchanged.",
+    ));
+
+    source.child("edit.txt").write_str("after, longer").unwrap();
+    source.child("new/nested.txt").write_str("new").unwrap();
+    fs::remove_file(source.child("drop.txt").path()).unwrap();
+
+    sync(&["--delete", "--dry-run"])
+        .stdout(predicate::str::contains("  - project/drop.txt"))
+        .stdout(predicate::str::contains(
+            "2 added, 1 updated, 1 deleted, 1 unchanged.",
+        ));
+    backup.child("project/drop.txt").assert("drop");
+
+    sync(&["--delete"])
+        .stdout(predicate::str::contains("  ~ project/edit.txt"))
+        .stdout(predicate::str::contains("  + project/new/nested.txt"));
+    backup.child("project/edit.txt").assert("after, longer");
+    backup.child("project/new/nested.txt").assert("new");
+    backup
+        .child("project/drop.txt")
+        .assert(predicate::path::missing());
+
+    sync(&["--checksum"]).stdout(predicate::str::contains(
+        "0 added, 0 updated, 0 deleted, 3 unchanged.",
+    ));
+}
+
+#[cfg(unix)]
+#[test]
+fn sync_mirrors_dangling_links_and_keeps_links_with_no_dereference() {
+    use std::os::unix::fs::symlink;
+
+    let workspace = TempDir::new().unwrap();
+    let config_dir = TempDir::new().unwrap();
+
+    let source = workspace.child("project");
+    source.child("data.txt").write_str("data").unwrap();
+    symlink("data.txt", source.child("link").path()).unwrap();
+    symlink("missing", source.child("dangling").path()).unwrap();
+    mark_paths(&config_dir, &[source.path()]);
+
+    let backup = workspace.child("backup");
+    backup.create_dir_all().unwrap();
+    let sync = |extra: &[&str]| {
+        let mut cmd = fuga_command(&config_dir);
+        cmd.arg("sync").args(extra).arg(backup.path());
+        cmd.assert().success()
+    };
+
+    sync(&[]).stdout(predicate::str::contains("  + project/dangling"));
+    assert_eq!(
+        read_link(backup.child("project/dangling").path()).unwrap(),
+        Path::new("missing")
+    );
+    assert!(!fs::symlink_metadata(backup.child("project/link").path())
+        .unwrap()
+        .is_symlink());
+    backup.child("project/link").assert("data");
+
+    sync(&[]).stdout(predicate::str::contains(
+        "0 added, 0 updated, 0 deleted, 3 unchanged.",
+    ));
+
+    sync(&["-P"]).stdout(predicate::str::contains("  ~ project/link"));
+    assert_eq!(
+        read_link(backup.child("project/link").path()).unwrap(),
+        Path::new("data.txt")
+    );
+    sync(&["-L"]).stdout(predicate::str::contains("  ~ project/link"));
+    backup.child("project/link").assert("data");
+
+    // Marked links are checked the way they are synced: followed, unless dangling
+    mark_paths(
+        &config_dir,
+        &[source.child("link").path(), source.child("dangling").path()],
+    );
+    let loose = workspace.child("loose");
+    loose.create_dir_all().unwrap();
+    let mut cmd = fuga_command(&config_dir);
+    cmd.arg("sync").arg(loose.path());
+    cmd.assert().success();
+    loose.child("link").assert("data");
+    assert_eq!(
+        read_link(loose.child("dangling").path()).unwrap(),
+        Path::new("missing")
+    );
+}
+
+#[test]
+fn sync_replaces_a_file_in_the_way_of_a_directory() {
+    let workspace = TempDir::new().unwrap();
+    let config_dir = TempDir::new().unwrap();
+
+    let source = workspace.child("project");
+    source.child("main.rs").write_str("fn main() {}").unwrap();
+    mark_paths(&config_dir, &[source.path()]);
+
+    let backup = workspace.child("backup");
+    backup.child("project").write_str("stale file").unwrap();
+
+    let mut sync = fuga_command(&config_dir);
+    sync.arg("sync").arg(backup.path());
+    sync.assert()
+        .success()
+        .stdout(predicate::str::contains("  ~ project/"));
+    backup.child("project/main.rs").assert("fn main() {}");
+}
+
+#[test]
+fn mark_filters_apply_to_copy_move_and_sync() {
+    let workspace = TempDir::new().unwrap();
+    let config_dir = TempDir::new().unwrap();
+
+    let source = workspace.child("project");
+    source.child(".git/HEAD").write_str("ref").unwrap();
+    source.child(".gitignore").write_str("*.log\n").unwrap();
+    source
+        .child("src/main.rs")
+        .write_str("fn main() {}")
+        .unwrap();
+    source.child("src/debug.log").write_str("noise").unwrap();
+    source.child("target/app").write_str("binary").unwrap();
+    source.child("keep.log").write_str("keep").unwrap();
+
+    let mut mark = fuga_command(&config_dir);
+    mark.args(["mark", "--exclude", "target/", "--respect-gitignore"])
+        .arg(source.path());
+    mark.assert().success().stdout(predicate::str::contains(
+        "exclude: target/; respects .gitignore",
+    ));
+
+    let mut list = fuga_command(&config_dir);
+    list.args(["mark", "--list"]);
+    list.assert()
+        .success()
+        .stdout(predicate::str::contains("exclude: target/"));
+
+    let copies = workspace.child("copies");
+    copies.create_dir_all().unwrap();
+    let mut copy = fuga_command(&config_dir);
+    copy.args(["copy", "--include", "keep.log"])
+        .arg(copies.path());
+    copy.assert().success();
+    copies.child("project/src/main.rs").assert("fn main() {}");
+    copies.child("project/keep.log").assert("keep");
+    for skipped in [".git", "target", "src/debug.log"] {
+        copies
+            .child("project")
+            .child(skipped)
+            .assert(predicate::path::missing());
+    }
+
+    let mirror = workspace.child("mirror");
+    mirror.child("project/target/old").write_str("old").unwrap();
+    let mut sync = fuga_command(&config_dir);
+    sync.args(["sync", "--delete"]).arg(mirror.path());
+    sync.assert()
+        .success()
+        .stdout(predicate::str::contains("  + project/src/main.rs"))
+        .stdout(predicate::str::contains("project/debug.log").not());
+    mirror.child("project/target/old").assert("old");
+
+    let moved = workspace.child("moved");
+    moved.create_dir_all().unwrap();
+    let mut r#move = fuga_command(&config_dir);
+    r#move.arg("move").arg(moved.path());
+    r#move
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("copied what the filters allow"));
+    moved.child("project/src/main.rs").assert("fn main() {}");
+    moved
+        .child("project/target")
+        .assert(predicate::path::missing());
+    source
+        .child("src/main.rs")
+        .assert(predicate::path::missing());
+    source.child("target/app").assert("binary");
+    source.child("src/debug.log").assert("noise");
+}
+
+#[test]
+fn undo_of_a_filtered_move_puts_the_moved_entries_back() {
+    let workspace = TempDir::new().unwrap();
+    let config_dir = TempDir::new().unwrap();
+
+    let source = workspace.child("project");
+    source
+        .child("src/main.rs")
+        .write_str("fn main() {}")
+        .unwrap();
+    source.child("notes.txt").write_str("notes").unwrap();
+    source.child("target/app").write_str("binary").unwrap();
+
+    let mut mark = fuga_command(&config_dir);
+    mark.args(["mark", "--exclude", "target/"])
+        .arg(source.path());
+    mark.assert().success();
+
+    let moved = workspace.child("moved");
+    moved.create_dir_all().unwrap();
+    let mut r#move = fuga_command(&config_dir);
+    r#move.arg("move").arg(moved.path());
+    r#move.assert().success();
+    moved.child("project/notes.txt").assert("notes");
+    source.child("notes.txt").assert(predicate::path::missing());
+    source.child("target/app").assert("binary");
+
+    // The excluded entries stayed behind, so the rest is merged back next to them
+    let mut undo = fuga_command(&config_dir);
+    undo.arg("undo");
+    undo.assert().success();
+    source.child("src/main.rs").assert("fn main() {}");
+    source.child("notes.txt").assert("notes");
+    source.child("target/app").assert("binary");
+    moved.child("project").assert(predicate::path::missing());
+}
+
+#[test]
+fn filtered_move_batch_reports_progress_of_what_it_copies() {
+    let workspace = TempDir::new().unwrap();
+    let config_dir = TempDir::new().unwrap();
+
+    let first = workspace.child("a");
+    first.child("one.txt").write_str("1234").unwrap();
+    first.child("skip/big").write_str("xxxxxxxx").unwrap();
+    let second = workspace.child("b");
+    second.child("two.txt").write_str("56").unwrap();
+    mark_paths(&config_dir, &[first.path(), second.path()]);
+
+    let dest = workspace.child("dest");
+    dest.create_dir_all().unwrap();
+    // Filtered directories are copied even on one filesystem, so they count toward the totals
+    let mut r#move = fuga_command(&config_dir);
+    r#move.args(["move", "--exclude", "skip/"]).arg(dest.path());
+    r#move
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("[100%] 6 B/6 B, 2/2 files"));
+    dest.child("a/one.txt").assert("1234");
+    dest.child("b/two.txt").assert("56");
+    first.child("skip/big").assert("xxxxxxxx");
+}
+
+#[cfg(unix)]
+#[test]
+fn copy_and_move_follow_or_keep_symbolic_links() {
+    use std::os::unix::fs::symlink;
+
+    let workspace = TempDir::new().unwrap();
+    let config_dir = TempDir::new().unwrap();
+
+    let data = workspace.child("data.txt");
+    data.write_str("data").unwrap();
+    let tree = workspace.child("tree");
+    tree.child("file.txt").write_str("file").unwrap();
+    symlink("../data.txt", tree.child("link").path()).unwrap();
+    symlink("missing", tree.child("dangling").path()).unwrap();
+    let marked_link = workspace.child("shortcut");
+    symlink(data.path(), marked_link.path()).unwrap();
+
+    let mut mark = fuga_command(&config_dir);
+    mark.arg("mark").arg(marked_link.path());
+    mark.assert()
+        .success()
+        .stdout(predicate::str::contains("[LINK]"));
+
+    // Copies follow links unless told otherwise
+    let followed = workspace.child("followed");
+    let mut copy = fuga_command(&config_dir);
+    copy.arg("copy").arg(followed.path());
+    copy.assert().success();
+    assert!(!fs::symlink_metadata(followed.path()).unwrap().is_symlink());
+    followed.assert("data");
+
+    let kept = workspace.child("kept");
+    let mut copy = fuga_command(&config_dir);
+    copy.args(["copy", "--no-dereference"]).arg(kept.path());
+    copy.assert().success();
+    assert_eq!(read_link(kept.path()).unwrap(), data.path());
+
+    // Links inside directories follow the same policy, dangling ones included
+    mark_paths(&config_dir, &[tree.path()]);
+    let copies = workspace.child("copies");
+    copies.create_dir_all().unwrap();
+    let mut copy = fuga_command(&config_dir);
+    copy.args(["copy", "-P"]).arg(copies.path());
+    copy.assert().success();
+    assert_eq!(
+        read_link(copies.child("tree/link").path()).unwrap(),
+        Path::new("../data.txt")
+    );
+    assert_eq!(
+        read_link(copies.child("tree/dangling").path()).unwrap(),
+        Path::new("missing")
+    );
+
+    // Measuring a batch for progress follows the same policy
+    mark_paths(&config_dir, &[tree.path(), data.path()]);
+    let batch = workspace.child("batch");
+    batch.create_dir_all().unwrap();
+    let mut copy = fuga_command(&config_dir);
+    copy.args(["copy", "-P"]).arg(batch.path());
+    copy.assert().success();
+    assert_eq!(
+        read_link(batch.child("tree/dangling").path()).unwrap(),
+        Path::new("missing")
+    );
+    batch.child("data.txt").assert("data");
+    mark_paths(&config_dir, &[tree.path()]);
+
+    let mut copy = fuga_command(&config_dir);
+    copy.arg("copy").arg(workspace.child("deep").path());
+    copy.assert()
+        .failure()
+        .stderr(predicate::str::contains("dangling"));
+
+    // Moves keep links as links
+    let moved = workspace.child("moved");
+    let mut r#move = fuga_command(&config_dir);
+    r#move.arg("move").arg(moved.path());
+    r#move.assert().success();
+    assert_eq!(
+        read_link(moved.child("link").path()).unwrap(),
+        Path::new("../data.txt")
+    );
+    tree.assert(predicate::path::missing());
+}
+
+#[test]
+fn mark_remove_and_toggle_edit_the_list_in_place() {
+    let workspace = TempDir::new().unwrap();
+    let config_dir = TempDir::new().unwrap();
+
+    for name in ["a.txt", "b.log", "c.log", "d.txt"] {
+        workspace.child(name).write_str(name).unwrap();
+    }
+    let path = |name: &str| workspace.child(name).path().to_str().unwrap().to_string();
+    // Paths are colored, so only the start and the end of the line are literal
+    let said = |subject: &str, verb: &str| {
+        predicate::str::is_match(format!("{subject}.* {verb}\\.")).unwrap()
+    };
+    let mark = |args: &[&str]| {
+        let mut cmd = fuga_command(&config_dir);
+        cmd.current_dir(workspace.path()).arg("mark").args(args);
+        cmd.assert().success()
+    };
+
+    mark(&["a.txt", "b.log", "c.log", "d.txt", "--note", "kept"]);
+    mark(&["--remove", "*.log", "1", "missing.txt"])
+        .stdout(said(&path("a.txt"), "unmarked"))
+        .stdout(said(&path("b.log"), "unmarked"))
+        .stdout(said(&path("c.log"), "unmarked"))
+        .stdout(said("missing.txt", "was not marked"))
+        .stdout(predicate::str::contains("now tracks 1 target(s)."));
+
+    mark(&["--toggle", "d.txt", "a.txt"])
+        .stdout(said(&path("d.txt"), "unmarked"))
+        .stdout(said(&path("a.txt"), "added"))
+        .stdout(predicate::str::contains("now tracks 1 target(s)."));
+
+    mark(&["--remove", "5"]).stdout(said("5", "was not marked"));
+    mark(&["--list"])
+        .stdout(predicate::str::contains(path("a.txt")))
+        .stdout(predicate::str::contains(path("d.txt")).not());
+
+    let mut toggle_missing = fuga_command(&config_dir);
+    toggle_missing
+        .current_dir(workspace.path())
+        .args(["mark", "--toggle", "a.txt", "missing.txt"]);
+    toggle_missing.assert().failure();
+    mark(&["--list"]).stdout(predicate::str::contains(path("a.txt")));
+}
+
+#[test]
+fn mark_glob_and_recursive_search_find_targets() {
+    let workspace = TempDir::new().unwrap();
+    let config_dir = TempDir::new().unwrap();
+
+    workspace.child("logs/a.log").write_str("a").unwrap();
+    workspace.child("logs/nested/b.log").write_str("b").unwrap();
+    workspace.child("notes.txt").write_str("notes").unwrap();
+    workspace.child("big.bin").write_binary(&[0; 2048]).unwrap();
+    let path = |name: &str| workspace.child(name).path().to_str().unwrap().to_string();
+    let mark = |args: &[&str]| {
+        let mut cmd = fuga_command(&config_dir);
+        cmd.current_dir(workspace.path()).arg("mark").args(args);
+        cmd.assert().success()
+    };
+    let list = || mark(&["--list"]).get_output().stdout.clone();
+
+    mark(&["--glob", "**/*.log"]).stdout(predicate::str::contains("now tracks 2 target(s)."));
+    let listed = String::from_utf8(list()).unwrap();
+    assert!(listed.contains(&path("logs/a.log")));
+    assert!(listed.contains(&path("logs/nested/b.log")));
+    assert!(!listed.contains(&path("notes.txt")));
+
+    mark(&["--add", "--recursive", "--type", "f", "--larger", "1K"])
+        .stdout(predicate::str::contains(path("big.bin")))
+        .stdout(predicate::str::contains("now tracks 3 target(s)."));
+
+    mark(&["--recursive", "--name", "*.txt", "--newer", "2000-01-01"])
+        .stdout(predicate::str::contains("now tracks 1 target(s)."));
+    mark(&["--recursive", "logs", "--type", "d"])
+        .stdout(predicate::str::contains(path("logs/nested")))
+        .stdout(predicate::str::contains("now tracks 1 target(s)."));
+
+    mark(&["--glob", "missing/*.log"]).stdout(predicate::str::contains("No paths matched"));
+    mark(&["--list"]).stdout(predicate::str::contains(path("logs/nested")));
+
+    let mut without_search = fuga_command(&config_dir);
+    without_search
+        .current_dir(workspace.path())
+        .args(["mark", "notes.txt", "--name", "*.txt"]);
+    without_search.assert().failure();
+}
+
+#[test]
+fn mark_reads_path_lists_and_prints_bare_paths() {
+    let workspace = TempDir::new().unwrap();
+    let config_dir = TempDir::new().unwrap();
+
+    for name in ["a.txt", "b c.txt", "d.txt"] {
+        workspace.child(name).write_str(name).unwrap();
+    }
+    workspace
+        .child("list.txt")
+        .write_str("d.txt\r\n\n")
+        .unwrap();
+    let path = |name: &str| workspace.child(name).path().to_str().unwrap().to_string();
+    let mark = |args: &[&str]| {
+        let mut cmd = fuga_command(&config_dir);
+        cmd.current_dir(workspace.path()).arg("mark").args(args);
+        cmd
+    };
+
+    mark(&["--stdin", "-0"])
+        .write_stdin("a.txt\0b c.txt\0")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("now tracks 2 target(s)."));
+    mark(&["--add", "--from-file", "list.txt"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("now tracks 3 target(s)."));
+
+    mark(&["--list", "--print0"])
+        .assert()
+        .success()
+        .stdout(format!(
+            "{}\0{}\0{}\0",
+            path("a.txt"),
+            path("b c.txt"),
+            path("d.txt")
+        ));
+    mark(&["--list", "--format", "plain"])
+        .assert()
+        .success()
+        .stdout(format!(
+            "{}\n{}\n{}\n",
+            path("a.txt"),
+            path("b c.txt"),
+            path("d.txt")
+        ));
+
+    mark(&["a.txt", "-0"]).assert().failure();
+    mark(&["--stdin"])
+        .write_stdin("missing.txt\n")
+        .assert()
+        .failure();
+    mark(&["--list", "--format", "plain"])
+        .assert()
+        .stdout(predicate::str::contains(path("d.txt")));
+}
+
+#[test]
+fn mark_check_and_prune_handle_stale_marks() {
+    let workspace = TempDir::new().unwrap();
+    let config_dir = TempDir::new().unwrap();
+
+    for name in ["kept.txt", "gone.txt", "swapped"] {
+        workspace.child(name).write_str(name).unwrap();
+    }
+    let mark = |args: &[&str]| {
+        let mut cmd = fuga_command(&config_dir);
+        cmd.current_dir(workspace.path()).arg("mark").args(args);
+        cmd.assert()
+    };
+
+    mark(&["kept.txt", "gone.txt", "swapped"]).success();
+    mark(&["--check"])
+        .success()
+        .stdout(predicate::str::contains(
+            "All 3 marked target(s) are still in place.",
+        ));
+
+    fs::remove_file(workspace.child("gone.txt").path()).unwrap();
+    fs::remove_file(workspace.child("swapped").path()).unwrap();
+    fs::create_dir(workspace.child("swapped").path()).unwrap();
+
+    mark(&["--check"])
+        .failure()
+        .stdout(predicate::str::contains("is missing."))
+        .stdout(predicate::str::contains(
+            "was a file and is now a directory.",
+        ))
+        .stderr(predicate::str::contains("2 of 3 mark(s) are stale."));
+
+    mark(&["--prune"])
+        .success()
+        .stdout(predicate::str::contains("unmarked, it is missing."))
+        .stdout(predicate::str::contains("now tracks 1 target(s)."));
+    mark(&["--list", "--format", "plain"])
+        .success()
+        .stdout(format!(
+            "{}\n",
+            workspace.child("kept.txt").path().display()
+        ));
+    mark(&["--prune"])
+        .success()
+        .stdout(predicate::str::contains("No stale marks to prune."));
+}